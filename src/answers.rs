@@ -0,0 +1,262 @@
+use crate::error::AocError;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Expected answers for one input of a day. Either part may be unknown.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// The contents of an answers file such as `data_files/answers.toml`.
+///
+/// Only the small subset of TOML we need is understood: a `[dayNN.<input>]` table per input
+/// holding `part1`/`part2` keys with basic (`"..."`) or multi-line (`"""..."""`) string values.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+    inputs: BTreeMap<(u8, String), Expected>,
+}
+
+impl Answers {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AocError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn get(&self, day: u8, input: &str) -> Option<&Expected> {
+        self.inputs.get(&(day, input.to_string()))
+    }
+}
+
+fn parse_basic_string(s: &str) -> Option<String> {
+    let inner = s.strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut res = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => res.push(match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                '"' => '"',
+                '\\' => '\\',
+                _ => return None,
+            }),
+            '"' => return None,
+            c => res.push(c),
+        }
+    }
+
+    Some(res)
+}
+
+impl FromStr for Answers {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = |line_idx: usize, msg: &str| AocError::parse(msg).at_line(line_idx + 1);
+
+        let mut inputs: BTreeMap<(u8, String), Expected> = BTreeMap::new();
+        let mut cur_input = None;
+
+        let mut lines = s.lines().enumerate();
+        while let Some((line_idx, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(table) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                let key = table
+                    .strip_prefix("day")
+                    .and_then(|t| t.split_once('.'))
+                    .and_then(|(day, name)| Some((day.parse().ok()?, name.to_string())))
+                    .filter(|(_, name)| !name.is_empty())
+                    .ok_or_else(|| err(line_idx, "expected a table like [day01.real]"))?;
+
+                if inputs.insert(key.clone(), Expected::default()).is_some() {
+                    return Err(err(line_idx, "duplicate input"));
+                }
+
+                cur_input = Some(key);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+                .ok_or_else(|| err(line_idx, "expected `key = value`"))?;
+
+            let value = if let Some(rest) = value.strip_prefix("\"\"\"") {
+                // Like TOML, a newline directly after the opening quotes is dropped.
+                let mut value = String::new();
+                let mut rest = rest.to_string();
+                if rest.is_empty() {
+                    rest = lines
+                        .next()
+                        .ok_or_else(|| err(line_idx, "unterminated string"))?
+                        .1
+                        .to_string();
+                }
+
+                loop {
+                    if let Some((last, _)) = rest.split_once("\"\"\"") {
+                        value.push_str(last);
+                        break value;
+                    }
+
+                    value.push_str(&rest);
+                    value.push('\n');
+                    rest = lines
+                        .next()
+                        .ok_or_else(|| err(line_idx, "unterminated string"))?
+                        .1
+                        .to_string();
+                }
+            } else {
+                parse_basic_string(value).ok_or_else(|| err(line_idx, "invalid string"))?
+            };
+
+            let expected = cur_input
+                .as_ref()
+                .and_then(|key| inputs.get_mut(key))
+                .ok_or_else(|| err(line_idx, "key outside of a [dayNN.<input>] table"))?;
+
+            match key {
+                "part1" => expected.part1 = Some(value),
+                "part2" => expected.part2 = Some(value),
+                _ => return Err(err(line_idx, "expected part1 or part2")),
+            }
+        }
+
+        Ok(Self { inputs })
+    }
+}
+
+/// Result of comparing an answer against the expected one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    Missing,
+}
+
+impl Verdict {
+    pub fn check(expected: Option<&str>, actual: &str) -> Self {
+        match expected {
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(_) => Verdict::Fail,
+            None => Verdict::Missing,
+        }
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => "PASS",
+            Verdict::Fail => "FAIL",
+            Verdict::Missing => "MISSING",
+        }
+        .fmt(f)
+    }
+}
+
+/// Test harness shared by every day: solve one of the checked in inputs and compare against
+/// the answers file.
+#[cfg(test)]
+pub fn test_input<D: crate::day::Day>(day: u8, name: &str) {
+    use std::io::BufReader;
+
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data_files");
+    let answers = Answers::load(data_dir.join("answers.toml")).unwrap();
+    let expected = answers.get(day, name).expect("No answers for input");
+    assert!(expected.part1.is_some() || expected.part2.is_some());
+
+    let path = crate::inputs::day_dir(&data_dir, day).join(format!("{}.txt", name));
+    let data = fs::read(path).unwrap();
+    let mut reader = BufReader::new(&data[..]);
+
+    let solved = D::new(&mut reader).unwrap();
+    if let Some(part1) = &expected.part1 {
+        assert_eq!(&solved.part1(), part1, "day {} {} part 1", day, name);
+    }
+    if let Some(part2) = &expected.part2 {
+        assert_eq!(&solved.part2(), part2, "day {} {} part 2", day, name);
+    }
+}
+
+/// Run `test_input` over every input of a day that has expected answers.
+#[cfg(test)]
+pub fn test_day<D: crate::day::Day>(day: u8) {
+    let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data_files");
+    let answers = Answers::load(data_dir.join("answers.toml")).unwrap();
+
+    let inputs = crate::inputs::discover(&data_dir, day).unwrap();
+    let mut tested = 0;
+    for input in inputs {
+        if answers.get(day, &input.name).is_some() {
+            test_input::<D>(day, &input.name);
+            tested += 1;
+        }
+    }
+
+    assert!(tested > 0, "No inputs with answers for day {}", day);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers: Answers = concat!(
+            "# comment\n",
+            "[day01.real]\n",
+            "part1 = \"12\"\n",
+            "part2 = \"a \\\"b\\\"\"\n",
+            "\n",
+            "[day13.example]\n",
+            "part2 = \"\"\"\n",
+            ".#\n",
+            "#.\n",
+            "\"\"\"\n",
+        )
+        .parse()
+        .unwrap();
+
+        let day01 = answers.get(1, "real").unwrap();
+        assert_eq!(day01.part1.as_deref(), Some("12"));
+        assert_eq!(day01.part2.as_deref(), Some("a \"b\""));
+
+        let day13 = answers.get(13, "example").unwrap();
+        assert_eq!(day13.part1, None);
+        assert_eq!(day13.part2.as_deref(), Some(".#\n#.\n"));
+
+        assert_eq!(answers.get(1, "example"), None);
+        assert_eq!(answers.get(2, "real"), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("part1 = \"1\"".parse::<Answers>().is_err());
+        assert!("[day1.a]\npart3 = \"1\"".parse::<Answers>().is_err());
+        assert!("[day1.a]\npart1 = 1".parse::<Answers>().is_err());
+        assert!("[day1.a]\npart1 = \"\"\"\nabc".parse::<Answers>().is_err());
+        assert!("[day1.a]\n[day1.a]".parse::<Answers>().is_err());
+        assert!("[day1]".parse::<Answers>().is_err());
+        assert!("[day1.]".parse::<Answers>().is_err());
+        assert!("[nope.a]".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn test_verdict() {
+        assert_eq!(Verdict::check(Some("1"), "1"), Verdict::Pass);
+        assert_eq!(Verdict::check(Some("1"), "2"), Verdict::Fail);
+        assert_eq!(Verdict::check(None, "2"), Verdict::Missing);
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::AocError;
+use crate::registry::DayEntry;
+use crate::Parts;
+
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Summary of repeated timings of a single phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Panics if `times` is empty.
+    pub fn new(times: &mut [Duration]) -> Self {
+        assert!(!times.is_empty());
+        times.sort_unstable();
+
+        let n = times.len();
+        let median = if n % 2 == 1 {
+            times[n / 2]
+        } else {
+            (times[n / 2 - 1] + times[n / 2]) / 2
+        };
+
+        Self {
+            min: times[0],
+            median,
+            max: times[n - 1],
+        }
+    }
+}
+
+/// Timings for one input. Parts that weren't run have no stats.
+pub struct BenchResult {
+    pub day: u8,
+    pub input: String,
+    pub parse: Stats,
+    pub part1: Option<Stats>,
+    pub part2: Option<Stats>,
+}
+
+impl BenchResult {
+    fn phases(&self) -> Vec<(&'static str, Stats)> {
+        [
+            ("parse", Some(self.parse)),
+            ("part1", self.part1),
+            ("part2", self.part2),
+        ]
+        .into_iter()
+        .filter_map(|(phase, stats)| Some((phase, stats?)))
+        .collect()
+    }
+}
+
+/// Construct and solve a day `reps` times over the same input, timing each phase separately.
+/// Each rep runs isolated on its own thread, so a panic or timeout fails just this day.
+pub fn bench_day(
+    entry: &'static DayEntry,
+    name: &str,
+    input: &[u8],
+    parts: Parts,
+    reps: usize,
+    timeout: Option<Duration>,
+) -> Result<BenchResult, AocError> {
+    let mut parse = Vec::with_capacity(reps);
+    let mut part1 = Vec::with_capacity(reps);
+    let mut part2 = Vec::with_capacity(reps);
+
+    for _ in 0..reps {
+        let res = entry.run_isolated(input.to_vec(), parts, timeout)?;
+        parse.push(res.parse_time);
+        part1.extend(res.part1.map(|part| part.time));
+        part2.extend(res.part2.map(|part| part.time));
+    }
+
+    Ok(BenchResult {
+        day: entry.number,
+        input: name.to_string(),
+        parse: Stats::new(&mut parse),
+        part1: parts.part1().then(|| Stats::new(&mut part1)),
+        part2: parts.part2().then(|| Stats::new(&mut part2)),
+    })
+}
+
+pub fn millis(d: Duration) -> String {
+    format!("{:.3}", d.as_secs_f64() * 1000.0)
+}
+
+pub const HEADER: [&str; 6] = [
+    "Day",
+    "Input",
+    "Phase",
+    "Min (ms)",
+    "Median (ms)",
+    "Max (ms)",
+];
+
+pub fn table_rows(results: &[BenchResult]) -> Vec<[String; 6]> {
+    results
+        .iter()
+        .flat_map(|res| {
+            res.phases().into_iter().map(|(phase, stats)| {
+                [
+                    res.day.to_string(),
+                    res.input.clone(),
+                    phase.to_string(),
+                    millis(stats.min),
+                    millis(stats.median),
+                    millis(stats.max),
+                ]
+            })
+        })
+        .collect()
+}
+
+/// Quote a field if it has a comma, quote or line break in it, doubling any quotes (RFC 4180)
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+pub fn write_csv<W: Write>(mut writer: W, results: &[BenchResult], reps: usize) -> io::Result<()> {
+    writeln!(writer, "day,input,phase,reps,min_ms,median_ms,max_ms")?;
+    for res in results {
+        for (phase, stats) in res.phases() {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                res.day,
+                csv_field(&res.input),
+                phase,
+                reps,
+                millis(stats.min),
+                millis(stats.median),
+                millis(stats.max)
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let ms = Duration::from_millis;
+
+        let stats = Stats::new(&mut [ms(5), ms(1), ms(3)]);
+        assert_eq!(
+            stats,
+            Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            }
+        );
+
+        let stats = Stats::new(&mut [ms(4), ms(1), ms(2), ms(8)]);
+        assert_eq!(
+            stats,
+            Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(8)
+            }
+        );
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let stats = Stats::new(&mut [Duration::from_millis(2)]);
+        let results = [BenchResult {
+            day: 1,
+            input: "big, \"odd\" name".to_string(),
+            parse: stats,
+            part1: None,
+            part2: None,
+        }];
+
+        let mut out = vec![];
+        write_csv(&mut out, &results, 1).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(
+            out.lines().nth(1),
+            Some("1,\"big, \"\"odd\"\" name\",parse,1,2.000,2.000,2.000")
+        );
+    }
+}
@@ -1,12 +1,82 @@
-use std::error::Error;
+use crate::error::AocError;
 use std::io::BufRead;
 
-/// A day takes in some reader and outputs two strings, one for each part
-pub trait Day {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>>
+/// A day takes in some reader and outputs two strings, one for each part.
+/// Days are `Send` so the runner can solve them on worker threads.
+pub trait Day: Send {
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError>
     where
         Self: Sized;
 
     fn part1(&self) -> String;
     fn part2(&self) -> String;
+
+    /// Fallible `part1`, for days that put off setup that can fail until a part needs it. The
+    /// runner calls this, so such days report an error instead of panicking.
+    fn try_part1(&self) -> Result<String, AocError> {
+        Ok(self.part1())
+    }
+
+    /// Fallible `part2`, see `try_part1`
+    fn try_part2(&self) -> Result<String, AocError> {
+        Ok(self.part2())
+    }
+
+    /// Construct the day behind a `DynDay` pointer. Fits the registry's `Constructor` type.
+    fn new_dyn(reader: &mut dyn BufRead) -> Result<Box<dyn DynDay>, AocError>
+    where
+        Self: Sized + 'static,
+    {
+        Ok(Box::new(Self::new(reader)?))
+    }
+}
+
+/// Object safe counterpart of `Day`, so days of different types can be stored and run together.
+/// Every `Day` is a `DynDay`; build one from a `&mut dyn BufRead` with `Day::new_dyn`.
+pub trait DynDay: Send {
+    fn part1(&self) -> String;
+    fn part2(&self) -> String;
+    fn try_part1(&self) -> Result<String, AocError>;
+    fn try_part2(&self) -> Result<String, AocError>;
+}
+
+impl<D: Day> DynDay for D {
+    fn part1(&self) -> String {
+        Day::part1(self)
+    }
+
+    fn part2(&self) -> String {
+        Day::part2(self)
+    }
+
+    fn try_part1(&self) -> Result<String, AocError> {
+        Day::try_part1(self)
+    }
+
+    fn try_part2(&self) -> Result<String, AocError> {
+        Day::try_part2(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::registry::Constructor;
+    use crate::{Day01, Day07};
+
+    #[test]
+    fn test_dyn_day() {
+        let inputs: [(&[u8], Constructor); 2] = [
+            (b"1\n3\n2\n", Day01::new_dyn),
+            (b"16,1,2,0,4,2,7,1,2,14\n", Day07::new_dyn),
+        ];
+
+        let days: Vec<Box<dyn DynDay>> = inputs
+            .into_iter()
+            .map(|(mut input, new)| new(&mut input).unwrap())
+            .collect();
+
+        assert_eq!(days[0].part1(), "1");
+        assert_eq!(days[1].part1(), "37");
+    }
 }
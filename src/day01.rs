@@ -1,51 +1,193 @@
 use crate::day::Day;
-use std::error::Error;
-use std::io::{self, BufRead};
-
-fn solve<T>(iter: &[T], offset: usize) -> usize
-where
-    T: std::cmp::PartialOrd,
-{
-    iter.windows(offset + 1)
-        .filter(|window| (window[0] < window[offset]))
-        .count()
+use crate::error::AocError;
+use crate::util;
+
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::num::NonZeroUsize;
+
+/// Statistics over the sums of every `window` consecutive depth readings, computed in one pass.
+/// Only the last `window` readings are kept, so arbitrarily long logs can be fed through `push`
+/// or `scan`. Windows are numbered from 0 by their first reading.
+#[derive(Debug, Clone)]
+pub struct DepthStats {
+    window: usize,
+    recent: VecDeque<i64>,
+    len: usize,
+    increases: usize,
+    run: usize,
+    longest_run: usize,
 }
 
+impl DepthStats {
+    /// Part 1 uses a window of 1 and part 2 a window of 3.
+    pub fn new(window: NonZeroUsize) -> Self {
+        let window = window.get();
+
+        Self {
+            window,
+            recent: VecDeque::with_capacity(window + 1),
+            len: 0,
+            increases: 0,
+            run: 0,
+            longest_run: 0,
+        }
+    }
+
+    /// Add the next reading. Returns the position of the window it completes if that window's
+    /// sum is smaller than the one before.
+    pub fn push(&mut self, depth: i64) -> Option<usize> {
+        self.len += 1;
+        self.recent.push_back(depth);
+        if self.recent.len() <= self.window {
+            // The first full window starts the first run
+            if self.recent.len() == self.window {
+                self.run = 1;
+                self.longest_run = 1;
+            }
+            return None;
+        }
+
+        // Neighboring windows share all but their ends, so compare those directly
+        let oldest = self.recent.pop_front().unwrap();
+        self.run = if oldest < depth {
+            self.increases += 1;
+            self.run + 1
+        } else {
+            1
+        };
+        self.longest_run = self.longest_run.max(self.run);
+
+        (depth < oldest).then_some(self.len - self.window)
+    }
+
+    /// Feed every reading from `reader`, one per line, calling `on_decrease` with the position of
+    /// each window with a smaller sum than the one before.
+    pub fn scan<R, F>(
+        reader: &mut R,
+        window: NonZeroUsize,
+        mut on_decrease: F,
+    ) -> Result<Self, AocError>
+    where
+        R: BufRead + ?Sized,
+        F: FnMut(usize),
+    {
+        let mut stats = Self::new(window);
+        for depth in util::parsed_lines(reader) {
+            if let Some(pos) = stats.push(depth?) {
+                on_decrease(pos);
+            }
+        }
+
+        Ok(stats)
+    }
+
+    /// Number of readings seen
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of windows with a larger sum than the window before
+    pub fn increases(&self) -> usize {
+        self.increases
+    }
+
+    /// Number of windows in the longest stretch of strictly increasing sums
+    pub fn longest_run(&self) -> usize {
+        self.longest_run
+    }
+}
+
+const SINGLE: NonZeroUsize = NonZeroUsize::new(1).unwrap();
+const TRIPLE: NonZeroUsize = NonZeroUsize::new(3).unwrap();
+
 pub struct Day01 {
-    nums: Vec<i64>,
+    /// Stats over single readings, for part 1
+    single: DepthStats,
+    /// Stats over sums of three readings, for part 2
+    triple: DepthStats,
+}
+
+impl Day01 {
+    pub fn single(&self) -> &DepthStats {
+        &self.single
+    }
+
+    pub fn triple(&self) -> &DepthStats {
+        &self.triple
+    }
 }
 
 impl Day for Day01 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let nums: Vec<i64> = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<Vec<i64>, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        // Both windows in one pass, so the readings never need to be held in memory
+        let mut single = DepthStats::new(SINGLE);
+        let mut triple = DepthStats::new(TRIPLE);
+        for depth in util::parsed_lines(reader) {
+            let depth = depth?;
+            single.push(depth);
+            triple.push(depth);
+        }
 
-        Ok(Self { nums })
+        Ok(Self { single, triple })
     }
 
     fn part1(&self) -> String {
-        solve(&self.nums, 1).to_string()
+        self.single.increases().to_string()
     }
 
     fn part2(&self) -> String {
-        solve(&self.nums, 3).to_string()
+        self.triple.increases().to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day01.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day01>(1);
+    }
+
+    #[test]
+    fn test_stats() {
+        let input = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+
+        let mut positions = vec![];
+        let stats = DepthStats::scan(&mut input.as_bytes(), SINGLE, |pos| positions.push(pos));
+        let stats = stats.unwrap();
+        assert_eq!(stats.len(), 10);
+        assert_eq!(stats.increases(), 7);
+        assert_eq!(stats.longest_run(), 4);
+        assert_eq!(positions, [4, 8]);
+
+        // Sums 607, 618, 618, 617, 647, 716, 769, 792
+        let mut positions = vec![];
+        let stats = DepthStats::scan(&mut input.as_bytes(), TRIPLE, |pos| positions.push(pos));
+        let stats = stats.unwrap();
+        assert_eq!(stats.increases(), 5);
+        assert_eq!(stats.longest_run(), 5);
+        assert_eq!(positions, [3]);
+
+        let whole = NonZeroUsize::new(10).unwrap();
+        let stats = DepthStats::scan(&mut input.as_bytes(), whole, |_| {}).unwrap();
+        assert_eq!((stats.increases(), stats.longest_run()), (0, 1));
+        let longer = NonZeroUsize::new(11).unwrap();
+        let stats = DepthStats::scan(&mut input.as_bytes(), longer, |_| {}).unwrap();
+        assert_eq!(stats.longest_run(), 0);
+
+        let err = DepthStats::scan(&mut "1\n2\nx\n".as_bytes(), SINGLE, |_| {}).unwrap_err();
+        assert_eq!(err.line(), Some(3));
 
-        let day = Day01::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "1529");
-        assert_eq!(day.part2(), "1567");
+        let day = Day01::new(&mut input.as_bytes()).unwrap();
+        assert_eq!((day.single().increases(), day.triple().increases()), (7, 5));
+        assert_eq!(day.triple().longest_run(), 5);
     }
 }
@@ -1,131 +1,195 @@
 use crate::day::Day;
-use std::error::Error;
-use std::fmt;
-use std::io::{self, BufRead};
+use crate::error::AocError;
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
-struct ParseCommandError(String);
-
-impl fmt::Display for ParseCommandError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
-    }
-}
-
-impl Error for ParseCommandError {}
-
-#[derive(Debug, Clone, Copy)]
-enum Command {
+/// One step of a submarine route
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
     Forward(i64),
     Down(i64),
     Up(i64),
+    /// Undo a `forward` of the same amount
+    Back(i64),
+    /// Return to the surface at the start, facing level
+    Reset,
 }
 
 impl FromStr for Command {
-    type Err = ParseCommandError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(' ').collect();
-        let val = parts[1]
+        if s == "reset" {
+            return Ok(Command::Reset);
+        }
+
+        let (cmd, val) = s.split_once(' ').ok_or_else(|| {
+            AocError::parse(format!("expected `<command> <amount>`, found {:?}", s))
+        })?;
+        let val = val
             .parse()
-            .map_err(|_| ParseCommandError(s.to_string()))?;
+            .map_err(|e| AocError::from(e).at_column(cmd.len() + 2))?;
 
-        match parts.get(0) {
-            Some(&"forward") => Ok(Command::Forward(val)),
-            Some(&"down") => Ok(Command::Down(val)),
-            Some(&"up") => Ok(Command::Up(val)),
-            _ => Err(ParseCommandError(s.to_string())),
+        match cmd {
+            "forward" => Ok(Command::Forward(val)),
+            "down" => Ok(Command::Down(val)),
+            "up" => Ok(Command::Up(val)),
+            "back" => Ok(Command::Back(val)),
+            _ => Err(AocError::parse(format!("unknown command {:?}", cmd)).at_column(1)),
         }
     }
 }
 
-struct Position {
-    horizontal: i64,
-    depth: i64,
-    aim: i64,
-}
-
-impl Position {
-    pub fn new() -> Self {
-        Position {
-            horizontal: 0,
-            depth: 0,
-            aim: 0,
+/// Parse a route, one command per line. Blank lines and anything after a `#` are ignored.
+pub fn parse_program<R: BufRead + ?Sized>(reader: &mut R) -> Result<Vec<Command>, AocError> {
+    let mut commands = vec![];
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        let code = line.split_once('#').map_or(line.as_str(), |(code, _)| code);
+        let trimmed = code.trim_start();
+        if trimmed.is_empty() {
+            continue;
         }
-    }
 
-    pub fn part1(&self) -> i64 {
-        self.horizontal * self.aim
+        let indent = code.len() - trimmed.len();
+        let command = trimmed
+            .trim_end()
+            .parse()
+            .map_err(|e: AocError| e.offset_column(indent).at_line(line_idx + 1))?;
+        commands.push(command);
     }
 
-    pub fn part2(&self) -> i64 {
+    Ok(commands)
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub horizontal: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+impl Position {
+    pub fn product(&self) -> i64 {
         self.horizontal * self.depth
     }
+}
 
-    fn update(&self, cmd: Command) -> Self {
-        match cmd {
-            Command::Forward(f) => Self {
-                horizontal: self.horizontal + f,
-                depth: self.depth + self.aim * f,
-                aim: self.aim,
+/// How `down` and `up` are interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    /// `down` and `up` change the depth directly (part 1)
+    Direct,
+    /// `down` and `up` tilt the submarine, and moving changes the depth by the aim (part 2)
+    Aim,
+}
+
+impl Model {
+    pub fn step(self, pos: Position, cmd: Command) -> Position {
+        match (self, cmd) {
+            (_, Command::Reset) => Position::default(),
+            (Model::Direct, Command::Forward(f)) => Position {
+                horizontal: pos.horizontal + f,
+                ..pos
+            },
+            (Model::Direct, Command::Back(b)) => Position {
+                horizontal: pos.horizontal - b,
+                ..pos
+            },
+            (Model::Direct, Command::Down(d)) => Position {
+                depth: pos.depth + d,
+                ..pos
             },
-            Command::Down(d) => Self {
-                horizontal: self.horizontal,
-                depth: self.depth,
-                aim: self.aim + d,
+            (Model::Direct, Command::Up(u)) => Position {
+                depth: pos.depth - u,
+                ..pos
             },
-            Command::Up(u) => Self {
-                horizontal: self.horizontal,
-                depth: self.depth,
-                aim: self.aim - u,
+            (Model::Aim, Command::Forward(f)) => Position {
+                horizontal: pos.horizontal + f,
+                depth: pos.depth + pos.aim * f,
+                ..pos
+            },
+            (Model::Aim, Command::Back(b)) => Position {
+                horizontal: pos.horizontal - b,
+                depth: pos.depth - pos.aim * b,
+                ..pos
+            },
+            (Model::Aim, Command::Down(d)) => Position {
+                aim: pos.aim + d,
+                ..pos
+            },
+            (Model::Aim, Command::Up(u)) => Position {
+                aim: pos.aim - u,
+                ..pos
             },
         }
     }
-}
 
-fn solve(cmds: &[Command]) -> Position {
-    cmds.iter()
-        .fold(Position::new(), |pos, &cmd| pos.update(cmd))
+    /// Where the route ends up
+    pub fn run(self, cmds: &[Command]) -> Position {
+        cmds.iter()
+            .fold(Position::default(), |pos, &cmd| self.step(pos, cmd))
+    }
+
+    /// Every position along the route, starting at the surface
+    pub fn trajectory(self, cmds: &[Command]) -> Vec<Position> {
+        let mut positions = vec![Position::default()];
+        for &cmd in cmds {
+            positions.push(self.step(*positions.last().unwrap(), cmd));
+        }
+
+        positions
+    }
 }
 
 pub struct Day02 {
-    end: Position,
+    commands: Vec<Command>,
 }
 
 impl Day for Day02 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let commands: Vec<Command> = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<Vec<Command>, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let commands = parse_program(reader)?;
 
-        let end = solve(&commands);
-
-        Ok(Self { end })
+        Ok(Self { commands })
     }
 
     fn part1(&self) -> String {
-        self.end.part1().to_string()
+        Model::Direct.run(&self.commands).product().to_string()
     }
 
     fn part2(&self) -> String {
-        self.end.part2().to_string()
+        Model::Aim.run(&self.commands).product().to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day02.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day02>(2);
+    }
 
-        let day = Day02::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "2187380");
-        assert_eq!(day.part2(), "2086357770");
+    #[test]
+    fn test_program() {
+        let route = "forward 5\n# dive\ndown 5\n\n  forward 8 # cruise\nback 3\nreset\nup 2\n";
+        let cmds = parse_program(&mut route.as_bytes()).unwrap();
+        assert_eq!(cmds.len(), 6);
+        assert_eq!(cmds[2], Command::Forward(8));
+
+        let path = Model::Aim.trajectory(&cmds);
+        assert_eq!(path.len(), 7);
+        assert_eq!((path[3].horizontal, path[3].depth), (13, 40));
+        assert_eq!((path[4].horizontal, path[4].depth), (10, 25));
+        assert_eq!(*path.last().unwrap(), Model::Aim.run(&cmds));
+        assert_eq!(Model::Direct.run(&cmds[..4]).product(), 50);
+
+        let err = parse_program(&mut "up 1\n\n  sideways 2\n".as_bytes()).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(3), Some(3)));
+        let err = parse_program(&mut "down x".as_bytes()).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(1), Some(6)));
     }
 }
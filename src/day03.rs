@@ -1,108 +1,279 @@
 use crate::day::Day;
-use std::error::Error;
-use std::io::{self, BufRead};
-
-fn part1(report: &[String]) -> i64 {
-    let num_bits = report[0].len();
-    assert!(report.iter().all(|x| x.len() == num_bits));
-
-    let mut freq = vec![0; num_bits];
-    for diag in report.iter() {
-        diag.bytes().enumerate().for_each(|(i, b)| {
-            if b == b'1' {
-                freq[i] += 1;
+use crate::error::AocError;
+use std::fmt::Write;
+use std::io::BufRead;
+
+/// The widest diagnostic that fits in a `u128`
+pub const MAX_BITS: usize = 128;
+
+/// Which life support rating to filter for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rating {
+    /// Keep the most common bit, 1 on ties
+    Oxygen,
+    /// Keep the least common bit, 0 on ties
+    Co2,
+}
+
+/// A diagnostic report, one binary number per line, all the same width.
+///
+/// Values are kept sorted, so every set of values sharing a prefix is a contiguous range, and the
+/// ones with a 0 in the next bit come before the ones with a 1. That turns each filter step of
+/// the life support ratings into a binary search.
+#[derive(Debug, Clone)]
+pub struct Report {
+    num_bits: usize,
+    values: Vec<u128>,
+}
+
+impl Report {
+    pub fn read<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let mut num_bits = None;
+        let mut values = vec![];
+
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let width = *num_bits.get_or_insert(line.len());
+            if !(1..=MAX_BITS).contains(&width) {
+                return Err(AocError::parse(format!(
+                    "expected between 1 and {} bits per line, found {}",
+                    MAX_BITS, width
+                ))
+                .at_line(line_idx + 1));
             }
-        });
+
+            if line.len() != width {
+                return Err(
+                    AocError::parse(format!("expected {} bits", width)).at_line(line_idx + 1)
+                );
+            }
+
+            let value = line
+                .bytes()
+                .enumerate()
+                .try_fold(0, |acc, (col_idx, b)| match b {
+                    b'0' => Ok(acc << 1),
+                    b'1' => Ok((acc << 1) | 1),
+                    _ => Err(AocError::at(line_idx + 1, col_idx + 1, "expected a 0 or 1")),
+                })?;
+            values.push(value);
+        }
+
+        let num_bits = num_bits.ok_or_else(|| AocError::parse("expected at least one line"))?;
+        values.sort_unstable();
+
+        Ok(Self { num_bits, values })
+    }
+
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
     }
 
-    let mut ans = 0;
-    for f in &freq {
-        ans <<= 1;
-        if f + f >= report.len() {
-            ans |= 1;
+    /// Number of values in the report
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The values in increasing order
+    pub fn values(&self) -> &[u128] {
+        &self.values
+    }
+
+    /// How many values have a 1 at each position, leftmost bit first
+    pub fn histogram(&self) -> Vec<usize> {
+        let mut counts = vec![0; self.num_bits];
+        for &value in &self.values {
+            for (pos, count) in counts.iter_mut().enumerate() {
+                *count += self.bit(value, pos) as usize;
+            }
         }
+
+        counts
     }
 
-    ans
-}
+    /// How many values in the sorted range `lo..hi` have a 1 at `pos`. Every value in the range
+    /// must share the bits left of `pos`.
+    pub fn ones_in(&self, lo: usize, hi: usize, pos: usize) -> usize {
+        hi - self.split(lo, hi, pos)
+    }
 
-fn _part2(report: &[String], idx: usize, win: u8) -> i64 {
-    if report.len() == 1 {
-        return i64::from_str_radix(&report[0], 2).unwrap();
+    /// Most common bit at every position, 1 on ties
+    pub fn gamma(&self) -> u128 {
+        self.histogram().into_iter().fold(0, |acc, ones| {
+            (acc << 1) | u128::from(2 * ones >= self.len())
+        })
     }
 
-    let num_bits = report[0].len();
-    assert!(report.iter().all(|x| x.len() == num_bits));
+    /// Least common bit at every position, which is gamma with every bit flipped
+    pub fn epsilon(&self) -> u128 {
+        self.gamma() ^ self.mask()
+    }
+
+    /// Narrow the report down one bit at a time, keeping the values with the most (oxygen) or
+    /// least (CO2) common bit, until one is left.
+    pub fn rating(&self, rating: Rating) -> u128 {
+        assert!(!self.is_empty());
+
+        let (mut lo, mut hi) = (0, self.len());
+        for pos in 0..self.num_bits {
+            if hi - lo == 1 {
+                break;
+            }
+
+            let mid = self.split(lo, hi, pos);
+            let (zeros, ones) = (mid - lo, hi - mid);
+            // A bit every remaining value shares can't narrow anything down
+            if zeros == 0 || ones == 0 {
+                continue;
+            }
 
-    let column_sum: usize = report
-        .iter()
-        .map(|s| {
-            if s.as_bytes().get(idx) == Some(&b'1') {
-                1
+            let keep_ones = match rating {
+                Rating::Oxygen => ones >= zeros,
+                Rating::Co2 => ones < zeros,
+            };
+            if keep_ones {
+                lo = mid;
             } else {
-                0
+                hi = mid;
             }
-        })
-        .sum();
-
-    let goal = if column_sum + column_sum >= report.len() {
-        win
-    } else {
-        win ^ 1
-    };
-    let sub_reports: Vec<String> = report
-        .iter()
-        .filter(|s| s.as_bytes().get(idx) == Some(&goal))
-        .cloned()
-        .collect();
-
-    _part2(&sub_reports, idx + 1, win)
+        }
+
+        self.values[lo]
+    }
+
+    fn mask(&self) -> u128 {
+        u128::MAX >> (MAX_BITS - self.num_bits)
+    }
+
+    /// The bit at `pos`, counting from the left
+    fn bit(&self, value: u128, pos: usize) -> bool {
+        (value >> (self.num_bits - 1 - pos)) & 1 == 1
+    }
+
+    /// First index in `lo..hi` with a 1 at `pos`
+    fn split(&self, lo: usize, hi: usize, pos: usize) -> usize {
+        lo + self.values[lo..hi].partition_point(|&value| !self.bit(value, pos))
+    }
 }
 
-fn part2(report: &[String], win: u8) -> i64 {
-    _part2(report, 0, win)
+/// The exact product of two ratings in decimal. Reports up to 128 bits wide can have products up
+/// to 256 bits, so anything past a `u128` is multiplied out on 64 bit limbs.
+fn product(a: u128, b: u128) -> String {
+    if let Some(prod) = a.checked_mul(b) {
+        return prod.to_string();
+    }
+
+    // Schoolbook multiplication, least significant limb first
+    let (a, b) = ([a as u64, (a >> 64) as u64], [b as u64, (b >> 64) as u64]);
+    let mut limbs = [0u64; 4];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let cur = u128::from(x) * u128::from(y) + u128::from(limbs[i + j]) + carry;
+            limbs[i + j] = cur as u64;
+            carry = cur >> 64;
+        }
+        limbs[i + 2] = carry as u64;
+    }
+
+    // Peel off 19 digits at a time, the most a u64 can hold, least significant first
+    const CHUNK: u128 = 10_000_000_000_000_000_000;
+    let mut chunks = vec![];
+    while limbs.iter().any(|&limb| limb != 0) {
+        let mut rem = 0;
+        for limb in limbs.iter_mut().rev() {
+            let cur = (rem << 64) | u128::from(*limb);
+            *limb = (cur / CHUNK) as u64;
+            rem = cur % CHUNK;
+        }
+        chunks.push(rem);
+    }
+
+    // The product overflowed a u128, so there are at least two chunks
+    let mut res = chunks.pop().unwrap().to_string();
+    for chunk in chunks.iter().rev() {
+        write!(res, "{:019}", chunk).unwrap();
+    }
+
+    res
 }
 
 pub struct Day03 {
-    report: Vec<String>,
+    report: Report,
 }
 
 impl Day for Day03 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let report: Vec<String> = reader.lines().collect::<io::Result<Vec<_>>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let report = Report::read(reader)?;
 
         Ok(Self { report })
     }
 
     fn part1(&self) -> String {
-        let gamma = part1(&self.report);
-        // hack: epsilon is the same as gamma with the bits flipped.
-        let num_bits = self.report[0].len();
-        let epsilon = ((1 << num_bits) - 1) ^ gamma;
-
-        (gamma * epsilon).to_string()
+        product(self.report.gamma(), self.report.epsilon())
     }
 
     fn part2(&self) -> String {
-        let oxygen = part2(&self.report, b'1');
-        let co2 = part2(&self.report, b'0');
+        let oxygen = self.report.rating(Rating::Oxygen);
+        let co2 = self.report.rating(Rating::Co2);
 
-        (oxygen * co2).to_string()
+        product(oxygen, co2)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day03.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day03>(3);
+    }
+
+    #[test]
+    fn test_report() {
+        const EXAMPLE: &str = "00100\n11110\n10110\n10111\n10101\n01111\n\
+                               00111\n11100\n10000\n11001\n00010\n01010\n";
+
+        let report = Report::read(&mut EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(report.histogram(), [7, 5, 8, 7, 5]);
+        assert_eq!(report.ones_in(0, report.len(), 0), 7);
+        assert_eq!((report.gamma(), report.epsilon()), (22, 9));
+        assert_eq!(report.rating(Rating::Oxygen), 23);
+        assert_eq!(report.rating(Rating::Co2), 10);
+
+        let wide = format!("1{}\n0{}1\n", "0".repeat(127), "0".repeat(126));
+        let report = Report::read(&mut wide.as_bytes()).unwrap();
+        assert_eq!(report.num_bits(), 128);
+        assert_eq!(report.values(), [1, 1 << 127]);
+        assert_eq!(report.rating(Rating::Oxygen), 1 << 127);
+        let day = Day03::new(&mut wide.as_bytes()).unwrap();
+        assert_eq!(
+            day.part1(),
+            "28948022309329048855892746252171976963147354982949671778132708698262398304254"
+        );
+
+        assert_eq!(product(22, 9), "198");
+        assert_eq!(
+            product(1 << 127, 2),
+            "340282366920938463463374607431768211456"
+        );
+        assert_eq!(
+            product(1 << 127, 3),
+            "510423550381407695195061911147652317184"
+        );
+        assert_eq!(
+            product(u128::MAX, u128::MAX),
+            "115792089237316195423570985008687907852589419931798687112530834793049593217025"
+        );
 
-        let day = Day03::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "738234");
-        assert_eq!(day.part2(), "3969126");
+        let err = Report::read(&mut "101\n121\n".as_bytes()).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(2), Some(2)));
     }
 }
@@ -1,117 +1,348 @@
 use crate::day::Day;
+use crate::error::{at_line, AocError};
+use crate::util::{self, Grid};
 use std::collections::HashMap;
-use std::error::Error;
-use std::io::{self, BufRead};
-
-type Bingo = Vec<Vec<u8>>;
-type BingoRef<'a> = &'a [Vec<u8>];
-
-fn solve_row(lookup: &HashMap<u8, u8>, board: BingoRef) -> u8 {
-    *board
-        .iter()
-        .map(|row| row.iter().map(|x| lookup.get(x).unwrap()).max().unwrap())
-        .min()
-        .unwrap()
-}
+use std::fmt::Write;
+use std::io::BufRead;
 
-fn transpose(board: BingoRef) -> Bingo {
-    let n = board.len();
-    (0..n)
-        .map(|idx| board.iter().map(|board| board[idx]).collect())
-        .collect()
+/// Which lines of marked cells win a board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Do the two diagonals count, on top of rows and columns
+    pub diagonals: bool,
 }
 
-fn get_time(lookup: &HashMap<u8, u8>, board: BingoRef) -> u8 {
-    // do the rows
-    let row_time = solve_row(lookup, board);
-    let flip_board = transpose(board);
-    let col_time = solve_row(lookup, &flip_board);
+impl Rules {
+    /// The positions of every winning line on an `n` by `n` board
+    fn lines(self, n: usize) -> Vec<Vec<(usize, usize)>> {
+        let mut lines: Vec<Vec<(usize, usize)>> = (0..n)
+            .flat_map(|i| {
+                [
+                    (0..n).map(|j| (i, j)).collect(),
+                    (0..n).map(|j| (j, i)).collect(),
+                ]
+            })
+            .collect();
 
-    std::cmp::min(row_time, col_time)
+        if self.diagonals {
+            lines.push((0..n).map(|i| (i, i)).collect());
+            lines.push((0..n).map(|i| (i, n - 1 - i)).collect());
+        }
+
+        lines
+    }
 }
 
-type CmpType<T> = fn(T, T) -> T;
-
-fn solve(nums: &[u8], boards: &[Bingo], cmp: CmpType<(u8, Bingo)>) -> u16 {
-    let lookup: HashMap<u8, u8> = nums
-        .iter()
-        .enumerate()
-        .map(|(i, &x)| (x, i as u8))
-        .collect();
-
-    let (time, best_board) = boards
-        .iter()
-        .map(|board| (get_time(&lookup, board), board.clone()))
-        .reduce(cmp)
-        .unwrap();
-
-    let total: u16 = best_board
-        .iter()
-        .flatten()
-        .filter(|cell| *lookup.get(cell).unwrap() > time)
-        .map(|&cell| u16::from(cell))
-        .sum();
-
-    total * u16::from(nums[time as usize])
+/// A board finishing a line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    /// Index of the board in the input
+    pub board: usize,
+    /// Index of the draw that finished the line
+    pub turn: usize,
+    /// The number that finished the line
+    pub number: u32,
+    /// Sum of the unmarked cells times the winning number
+    pub score: u64,
 }
 
-pub struct Day04 {
-    nums: Vec<u8>,
-    bingos: Vec<Bingo>,
+#[derive(Debug, Clone)]
+pub struct Bingo {
+    draws: Vec<u32>,
+    boards: Vec<Grid<u32>>,
 }
 
-impl Day for Day04 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut nums = String::new();
-        reader.read_line(&mut nums)?;
-
-        let nums = nums
-            .trim()
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<Vec<u8>, _>>()?;
-        let boards: Vec<String> = reader.lines().collect::<io::Result<Vec<String>>>()?;
-
-        let bingos: Vec<Bingo> = boards
-            .chunks_exact(6)
-            .map(|board| {
-                board
+impl Bingo {
+    /// Read the draws on the first line, then square boards separated by blank lines. Every board
+    /// must be the same size.
+    pub fn read<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let blocks = util::read_blocks(reader)?;
+
+        let (draws, blocks) = match blocks.split_first() {
+            Some((draws, blocks)) if draws.first_line == 1 && draws.lines.len() == 1 => (
+                util::parse_csv(&draws.lines[0]).map_err(at_line(1))?,
+                blocks,
+            ),
+            _ => {
+                return Err(
+                    AocError::parse("expected a line of numbers, then a blank line").at_line(1),
+                )
+            }
+        };
+
+        let size = blocks
+            .first()
+            .map(|block| block.lines.len())
+            .ok_or_else(|| AocError::parse("expected at least one board").at_line(2))?;
+
+        let boards = blocks
+            .iter()
+            .map(|block| {
+                if block.lines.len() != size {
+                    return Err(AocError::parse(format!(
+                        "expected {} rows per board, found {}",
+                        size,
+                        block.lines.len()
+                    ))
+                    .at_line(block.first_line));
+                }
+
+                let mut cells = Vec::with_capacity(size * size);
+                for (line, row) in block.numbered() {
+                    let len = cells.len();
+                    for cell in row.split_whitespace() {
+                        cells.push(cell.parse().map_err(at_line(line))?);
+                    }
+
+                    if cells.len() - len != size {
+                        return Err(AocError::parse(format!(
+                            "expected {} numbers per row, found {}",
+                            size,
+                            cells.len() - len
+                        ))
+                        .at_line(line));
+                    }
+                }
+
+                Ok(Grid::from_fn(size, size, |(row, col)| {
+                    cells[row * size + col]
+                }))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { draws, boards })
+    }
+
+    pub fn draws(&self) -> &[u32] {
+        &self.draws
+    }
+
+    pub fn boards(&self) -> &[Grid<u32>] {
+        &self.boards
+    }
+
+    /// Every board that wins, in the order they win. Boards winning on the same turn are in input
+    /// order, and boards that never win are left out.
+    pub fn rank(&self, rules: Rules) -> Vec<Win> {
+        // When each number is first drawn
+        let mut drawn_at = HashMap::new();
+        for (turn, &number) in self.draws.iter().enumerate() {
+            drawn_at.entry(number).or_insert(turn);
+        }
+
+        let lines = rules.lines(self.boards.first().map_or(0, Grid::width));
+
+        let mut wins: Vec<Win> = self
+            .boards
+            .iter()
+            .enumerate()
+            .filter_map(|(board_idx, board)| {
+                // A line is done once its last number is drawn, a board once its first line is
+                let turn = lines
                     .iter()
-                    .skip(1)
-                    .map(|row| {
-                        row.trim()
-                            .split_whitespace()
-                            .map(|x| x.parse().unwrap())
-                            .collect()
+                    .filter_map(|line| {
+                        line.iter()
+                            .map(|&pos| drawn_at.get(&board[pos]).copied())
+                            .try_fold(0, |acc, turn| Some(acc.max(turn?)))
                     })
-                    .collect()
+                    .min()?;
+
+                let unmarked: u64 = board
+                    .iter()
+                    .filter(|(_, cell)| drawn_at.get(cell).is_none_or(|&t| t > turn))
+                    .map(|(_, &cell)| u64::from(cell))
+                    .sum();
+                let number = self.draws[turn];
+
+                Some(Win {
+                    board: board_idx,
+                    turn,
+                    number,
+                    score: unmarked * u64::from(number),
+                })
             })
             .collect();
 
-        Ok(Self { nums, bingos })
+        wins.sort_by_key(|win| (win.turn, win.board));
+
+        wins
+    }
+
+    /// Step through the draws one at a time
+    pub fn replay(&self, rules: Rules) -> Replay<'_> {
+        let size = self.boards.first().map_or(0, Grid::width);
+
+        Replay {
+            bingo: self,
+            lines: rules.lines(size),
+            turn: 0,
+            marked: vec![Grid::new(size, size, false); self.boards.len()],
+            won: vec![false; self.boards.len()],
+        }
+    }
+}
+
+/// What happened on one turn of a replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub turn: usize,
+    pub number: u32,
+    /// Boards that finished their first line this turn
+    pub winners: Vec<usize>,
+}
+
+/// A game in progress, see `Bingo::replay`. Each call to `next` draws a number.
+pub struct Replay<'a> {
+    bingo: &'a Bingo,
+    lines: Vec<Vec<(usize, usize)>>,
+    turn: usize,
+    marked: Vec<Grid<bool>>,
+    won: Vec<bool>,
+}
+
+impl Replay<'_> {
+    /// The marked cells of a board so far
+    pub fn marked(&self, board: usize) -> &Grid<bool> {
+        &self.marked[board]
+    }
+
+    pub fn has_won(&self, board: usize) -> bool {
+        self.won[board]
+    }
+
+    /// A board with its marked numbers in brackets
+    pub fn render(&self, board: usize) -> String {
+        let numbers = &self.bingo.boards[board];
+        let width = numbers
+            .iter()
+            .map(|(_, n)| n.to_string().len())
+            .max()
+            .unwrap_or(0);
+
+        let mut res = String::new();
+        for (row, marks) in numbers.rows().zip(self.marked[board].rows()) {
+            for (idx, (number, &marked)) in row.iter().zip(marks).enumerate() {
+                let (open, close) = if marked { ('[', ']') } else { (' ', ' ') };
+                let sep = if idx == 0 { "" } else { " " };
+                write!(res, "{}{}{:>width$}{}", sep, open, number, close).unwrap();
+            }
+            res.push('\n');
+        }
+
+        res
+    }
+}
+
+impl Iterator for Replay<'_> {
+    type Item = Step;
+
+    fn next(&mut self) -> Option<Step> {
+        let &number = self.bingo.draws.get(self.turn)?;
+        let turn = self.turn;
+        self.turn += 1;
+
+        let mut winners = vec![];
+        for (board_idx, board) in self.bingo.boards.iter().enumerate() {
+            let marked = &mut self.marked[board_idx];
+            for (pos, &cell) in board.iter() {
+                if cell == number {
+                    marked[pos] = true;
+                }
+            }
+
+            if !self.won[board_idx]
+                && self
+                    .lines
+                    .iter()
+                    .any(|line| line.iter().all(|&pos| marked[pos]))
+            {
+                self.won[board_idx] = true;
+                winners.push(board_idx);
+            }
+        }
+
+        Some(Step {
+            turn,
+            number,
+            winners,
+        })
+    }
+}
+
+pub struct Day04 {
+    /// Never empty, both parts need a winner
+    ranking: Vec<Win>,
+}
+
+impl Day for Day04 {
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let bingo = Bingo::read(reader)?;
+
+        let ranking = bingo.rank(Rules::default());
+        if ranking.is_empty() {
+            return Err(AocError::parse("no board ever wins"));
+        }
+
+        Ok(Self { ranking })
     }
 
     fn part1(&self) -> String {
-        solve(&self.nums, &self.bingos, std::cmp::min::<(u8, Bingo)>).to_string()
+        self.ranking[0].score.to_string()
     }
 
     fn part2(&self) -> String {
-        solve(&self.nums, &self.bingos, std::cmp::max::<(u8, Bingo)>).to_string()
+        self.ranking[self.ranking.len() - 1].score.to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day04.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day04>(4);
+    }
 
-        let day = Day04::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "38913");
-        assert_eq!(day.part2(), "16836");
+    #[test]
+    fn test_bingo() {
+        let input = "5,1,9,3,7\n\n1 2 3\n4 5 6\n7 8 9\n\n9 1 4\n8 7 6\n2 3 5\n\n10 11\n";
+        let err = Bingo::read(&mut input.as_bytes()).unwrap_err();
+        assert_eq!(err.line(), Some(11));
+
+        let (input, _) = input.split_at(input.len() - 7);
+        let bingo = Bingo::read(&mut input.as_bytes()).unwrap();
+        assert_eq!(bingo.boards().len(), 2);
+
+        // Only the second board finishes a row or column, the 1-7-3 column on the 5th draw
+        let ranking = bingo.rank(Rules::default());
+        assert_eq!(ranking.len(), 1);
+        assert_eq!((ranking[0].board, ranking[0].turn), (1, 4));
+        assert_eq!(ranking[0].score, (4 + 8 + 6 + 2) * 7);
+
+        // The first board finishes the 1-5-9 diagonal on the 3rd draw
+        let ranking = bingo.rank(Rules { diagonals: true });
+        assert_eq!(ranking.len(), 2);
+        assert_eq!((ranking[0].board, ranking[0].turn), (0, 2));
+        assert_eq!(ranking[1].board, 1);
+
+        let mut replay = bingo.replay(Rules { diagonals: true });
+        let steps: Vec<Step> = replay.by_ref().take(3).collect();
+        assert_eq!(steps[2].winners, [0]);
+        assert!(replay.has_won(0) && !replay.has_won(1));
+        assert_eq!(replay.render(0), "[1]  2   3 \n 4  [5]  6 \n 7   8  [9]\n");
+        assert_eq!(replay.map(|step| step.winners).last(), Some(vec![1]));
+
+        let err = Bingo::read(&mut "1\n\n1 2\n3\n".as_bytes()).unwrap_err();
+        assert_eq!(err.line(), Some(4));
+    }
+
+    #[test]
+    fn test_no_winner() {
+        // A bingo board that never wins has no score
+        let err = Day04::new(&mut "1\n\n2\n".as_bytes()).err().unwrap();
+        assert_eq!(err.to_string(), "no board ever wins");
     }
 }
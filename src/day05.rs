@@ -1,66 +1,315 @@
 use crate::day::Day;
-use crate::util::{ParseInputError, Point};
+use crate::error::AocError;
+use crate::util::{self, Point};
 
 use std::collections::HashMap;
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::str::FromStr;
 
-fn signum(x: i64) -> i64 {
-    // TODO: use the num crate version
-    match x {
-        x if x > 0 => 1,
-        x if x < 0 => -1,
-        _ => 0,
+/// A line of vents. Only horizontal, vertical and 45° diagonal lines are allowed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Line {
+    pub p0: Point,
+    pub p1: Point,
+}
+
+impl FromStr for Line {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const ARROW: &str = " -> ";
+
+        let (p0, p1) = s
+            .split_once(ARROW)
+            .ok_or_else(|| AocError::parse(format!("expected `x,y -> x,y`, found {:?}", s)))?;
+
+        let offset = p0.len() + ARROW.len();
+        let p0 = p0.parse()?;
+        let p1 = p1.parse().map_err(|e: AocError| e.offset_column(offset))?;
+
+        let line = Line { p0, p1 };
+        if line.family().is_none() {
+            return Err(AocError::parse(
+                "expected a horizontal, vertical or diagonal line",
+            ));
+        }
+
+        Ok(line)
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-struct Line {
-    p0: Point,
-    p1: Point,
+/// The four directions a line can run in. Every cell is on exactly one carrier of each.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+enum Family {
+    /// `y = c`
+    Horizontal,
+    /// `x = c`
+    Vertical,
+    /// `x - y = c`
+    Diagonal,
+    /// `x + y = c`
+    AntiDiagonal,
 }
 
-impl FromStr for Line {
-    type Err = ParseInputError;
+impl Family {
+    const ALL: [Family; 4] = [
+        Family::Horizontal,
+        Family::Vertical,
+        Family::Diagonal,
+        Family::AntiDiagonal,
+    ];
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let ps: Vec<Point> = s.split(" -> ").map(str::parse).collect::<Result<_, _>>()?;
+    /// `(u, v)` such that the carriers of this family are `u * x + v * y = c`
+    fn coeffs(self) -> (i64, i64) {
+        match self {
+            Family::Horizontal => (0, 1),
+            Family::Vertical => (1, 0),
+            Family::Diagonal => (1, -1),
+            Family::AntiDiagonal => (1, 1),
+        }
+    }
+
+    fn carrier(self, p: Point) -> i64 {
+        let (u, v) = self.coeffs();
+        u * p.x + v * p.y
+    }
 
-        assert!(ps.len() == 2);
-        let p0 = ps[0];
-        let p1 = ps[1];
+    /// Position along the carrier. Distinct cells on a carrier have distinct positions.
+    fn param(self, p: Point) -> i64 {
+        match self {
+            Family::Vertical => p.y,
+            _ => p.x,
+        }
+    }
 
-        Ok(Line { p0, p1 })
+    /// The cell at `param` on the carrier `c`
+    fn point(self, c: i64, param: i64) -> Point {
+        match self {
+            Family::Horizontal => Point::new(param, c),
+            Family::Vertical => Point::new(c, param),
+            Family::Diagonal => Point::new(param, param - c),
+            Family::AntiDiagonal => Point::new(param, c - param),
+        }
     }
 }
 
 impl Line {
-    fn get_points(&self) -> Vec<Point> {
-        let dx = signum(self.p1.x - self.p0.x);
-        let dy = signum(self.p1.y - self.p0.y);
-
-        let mut res = vec![self.p0];
-        let mut p = self.p0;
-        while p != self.p1 {
-            p.x += dx;
-            p.y += dy;
-            res.push(p);
+    /// Which way the line runs. A single cell counts as horizontal.
+    fn family(&self) -> Option<Family> {
+        let d = self.p1 - self.p0;
+        match (d.x, d.y) {
+            (_, 0) => Some(Family::Horizontal),
+            (0, _) => Some(Family::Vertical),
+            (dx, dy) if dx == dy => Some(Family::Diagonal),
+            (dx, dy) if dx == -dy => Some(Family::AntiDiagonal),
+            _ => None,
         }
+    }
 
-        res
+    pub fn is_axis_aligned(&self) -> bool {
+        self.p0.x == self.p1.x || self.p0.y == self.p1.y
     }
 }
 
-fn solve(lines: &[Line]) -> i64 {
-    let mut freq = HashMap::new();
-    for line in lines.iter() {
-        for pt in line.get_points() {
-            freq.insert(pt, freq.get(&pt).unwrap_or(&0) + 1);
+/// A stretch of cells on one carrier, all covered by the same number of lines
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    family: Family,
+    carrier: i64,
+    lo: i64,
+    hi: i64,
+    coverage: u32,
+}
+
+impl Run {
+    fn len(&self) -> u64 {
+        (self.hi - self.lo + 1) as u64
+    }
+
+    fn contains(&self, p: Point) -> bool {
+        self.family.carrier(p) == self.carrier
+            && (self.lo..=self.hi).contains(&self.family.param(p))
+    }
+
+    fn x_range(&self) -> (i64, i64) {
+        match self.family {
+            Family::Vertical => (self.carrier, self.carrier),
+            _ => (self.lo, self.hi),
+        }
+    }
+
+    /// The cell where the carriers of two runs from different families cross, if it's on both
+    fn crossing(&self, other: &Run) -> Option<Point> {
+        let (u0, v0) = self.family.coeffs();
+        let (u1, v1) = other.family.coeffs();
+
+        // Cramer's rule, the crossing has to land on integer coordinates
+        let det = u0 * v1 - u1 * v0;
+        let x = self.carrier * v1 - other.carrier * v0;
+        let y = u0 * other.carrier - u1 * self.carrier;
+        if det == 0 || x % det != 0 || y % det != 0 {
+            return None;
+        }
+
+        let p = Point::new(x / det, y / det);
+        (self.contains(p) && other.contains(p)).then_some(p)
+    }
+}
+
+/// How many lines cover each cell, without visiting every cell.
+///
+/// Collinear lines only overlap within a carrier, so a 1D sweep over the line ends on each
+/// carrier splits it into runs of equal coverage. Lines in different families meet in at most one
+/// cell each, which a sweep over x finds. Only those crossing cells are stored, so memory scales
+/// with the number of lines and crossings rather than line lengths.
+#[derive(Debug, Clone)]
+pub struct Overlaps {
+    runs: Vec<Run>,
+    /// Coverage by family at every cell where runs of different families meet
+    crossings: HashMap<Point, [u32; 4]>,
+}
+
+impl Overlaps {
+    pub fn new(lines: &[Line]) -> Self {
+        let runs = Self::sweep_carriers(lines);
+        let crossings = Self::find_crossings(&runs);
+
+        Self { runs, crossings }
+    }
+
+    fn sweep_carriers(lines: &[Line]) -> Vec<Run> {
+        // Coverage changes at the start of a line and just past its end
+        let mut events: HashMap<(Family, i64), Vec<(i64, i32)>> = HashMap::new();
+        for line in lines {
+            let family = line.family().expect("lines are checked when parsed");
+            let (a, b) = (family.param(line.p0), family.param(line.p1));
+            let carrier_events = events.entry((family, family.carrier(line.p0))).or_default();
+            carrier_events.push((a.min(b), 1));
+            carrier_events.push((a.max(b) + 1, -1));
+        }
+
+        let mut runs = vec![];
+        for ((family, carrier), mut carrier_events) in events {
+            carrier_events.sort_unstable();
+
+            let mut coverage = 0;
+            for (idx, &(param, delta)) in carrier_events.iter().enumerate() {
+                coverage += delta;
+                let next = carrier_events.get(idx + 1).map(|&(next, _)| next);
+                if coverage > 0 && next != Some(param) {
+                    runs.push(Run {
+                        family,
+                        carrier,
+                        lo: param,
+                        hi: next.expect("every line ends") - 1,
+                        coverage: coverage as u32,
+                    });
+                }
+            }
+        }
+
+        runs
+    }
+
+    fn find_crossings(runs: &[Run]) -> HashMap<Point, [u32; 4]> {
+        let mut order: Vec<&Run> = runs.iter().collect();
+        order.sort_unstable_by_key(|run| run.x_range());
+
+        // Runs of one family never cross, so each family keeps its own active set and a run is
+        // only checked against the other three
+        let mut crossings = HashMap::new();
+        let mut active: [Vec<&Run>; 4] = Default::default();
+        for run in order {
+            let (x_lo, _) = run.x_range();
+
+            for family in Family::ALL
+                .into_iter()
+                .filter(|&family| family != run.family)
+            {
+                let others = &mut active[family as usize];
+                others.retain(|other| other.x_range().1 >= x_lo);
+
+                for other in others.iter() {
+                    if let Some(p) = run.crossing(other) {
+                        let coverage = crossings.entry(p).or_insert([0; 4]);
+                        coverage[run.family as usize] = run.coverage;
+                        coverage[other.family as usize] = other.coverage;
+                    }
+                }
+            }
+            active[run.family as usize].push(run);
+        }
+
+        crossings
+    }
+
+    /// Number of lines covering `p`
+    pub fn coverage(&self, p: Point) -> u32 {
+        if let Some(coverage) = self.crossings.get(&p) {
+            return coverage.iter().sum();
         }
+
+        Family::ALL
+            .iter()
+            .filter_map(|&family| {
+                self.runs
+                    .iter()
+                    .find(|run| run.family == family && run.contains(p))
+            })
+            .map(|run| run.coverage)
+            .sum()
     }
 
-    freq.values().filter(|&&v| v > 1).count() as i64
+    /// Number of cells covered by at least `k` lines
+    pub fn count_at_least(&self, k: u32) -> u64 {
+        // Count every run, then fix up the crossings which may be counted several times over
+        let in_runs: u64 = self
+            .runs
+            .iter()
+            .filter(|run| run.coverage >= k)
+            .map(Run::len)
+            .sum();
+
+        let counted = self
+            .crossings
+            .values()
+            .flatten()
+            .filter(|&&c| c > 0 && c >= k)
+            .count() as u64;
+        let actual = self
+            .crossings
+            .values()
+            .filter(|coverage| coverage.iter().sum::<u32>() >= k)
+            .count() as u64;
+
+        in_runs - counted + actual
+    }
+
+    /// Every cell covered by at least `k` lines, in `(x, y)` order. Unlike `count_at_least` this
+    /// visits every such cell.
+    pub fn points_at_least(&self, k: u32) -> Vec<Point> {
+        let mut points: Vec<Point> = self
+            .runs
+            .iter()
+            .filter(|run| run.coverage >= k)
+            .flat_map(|run| (run.lo..=run.hi).map(|param| run.family.point(run.carrier, param)))
+            .filter(|p| !self.crossings.contains_key(p))
+            .chain(
+                self.crossings
+                    .iter()
+                    .filter(|(_, coverage)| coverage.iter().sum::<u32>() >= k)
+                    .map(|(&p, _)| p),
+            )
+            .collect();
+
+        points.sort_unstable_by_key(|p| (p.x, p.y));
+        points
+    }
+
+    /// Cells covered by more than one line
+    pub fn overlap_points(&self) -> Vec<Point> {
+        self.points_at_least(2)
+    }
 }
 
 pub struct Day05 {
@@ -68,11 +317,8 @@ pub struct Day05 {
 }
 
 impl Day for Day05 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let lines = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<Vec<_>, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let lines = util::parse_lines(reader)?;
 
         Ok(Self { lines })
     }
@@ -81,30 +327,73 @@ impl Day for Day05 {
         let hv_lines: Vec<Line> = self
             .lines
             .iter()
-            .filter(|l| l.p0.x == l.p1.x || l.p0.y == l.p1.y)
-            .cloned()
+            .filter(|l| l.is_axis_aligned())
+            .copied()
             .collect();
 
-        solve(&hv_lines).to_string()
+        Overlaps::new(&hv_lines).count_at_least(2).to_string()
     }
 
     fn part2(&self) -> String {
-        solve(&self.lines).to_string()
+        Overlaps::new(&self.lines).count_at_least(2).to_string()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day05.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day05>(5);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let input = "0,9 -> 5,9\n8,0 -> 0,8\n9,4 -> 3,4\n2,2 -> 2,1\n7,0 -> 7,4\n\
+                     6,4 -> 2,0\n0,9 -> 2,9\n3,4 -> 1,4\n0,0 -> 8,8\n5,5 -> 8,2\n";
+        let lines: Vec<Line> = util::parse_lines(&mut input.as_bytes()).unwrap();
+
+        // Compare against covering every cell one at a time
+        let mut freq: HashMap<Point, u32> = HashMap::new();
+        for line in &lines {
+            let step = (line.p1 - line.p0).signum();
+            let mut p = line.p0;
+            *freq.entry(p).or_default() += 1;
+            while p != line.p1 {
+                p += step;
+                *freq.entry(p).or_default() += 1;
+            }
+        }
+
+        let overlaps = Overlaps::new(&lines);
+        for k in 1..=4 {
+            let mut expected: Vec<Point> = freq
+                .iter()
+                .filter(|(_, &c)| c >= k)
+                .map(|(&p, _)| p)
+                .collect();
+            expected.sort_unstable_by_key(|p| (p.x, p.y));
+
+            assert_eq!(overlaps.count_at_least(k), expected.len() as u64);
+            assert_eq!(overlaps.points_at_least(k), expected);
+        }
+        assert_eq!(overlaps.overlap_points().len(), 12);
+        for (&p, &c) in &freq {
+            assert_eq!(overlaps.coverage(p), c);
+        }
+        assert_eq!(overlaps.coverage(Point::new(9, 9)), 0);
+
+        // Far apart lines are cheap no matter how long they are
+        let long = [
+            "0,0 -> 4000000,4000000".parse().unwrap(),
+            "0,4000000 -> 4000000,0".parse().unwrap(),
+        ];
+        assert_eq!(Overlaps::new(&long).count_at_least(2), 1);
+        assert_eq!(Overlaps::new(&long).count_at_least(1), 8000001);
 
-        let day = Day05::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "5585");
-        assert_eq!(day.part2(), "17193");
+        assert!("0,0 -> 1,2".parse::<Line>().is_err());
     }
 }
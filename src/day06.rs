@@ -1,5 +1,6 @@
 use crate::day::Day;
-use std::error::Error;
+use crate::error::AocError;
+use crate::util;
 use std::io::BufRead;
 
 type LFState = [u64; 9];
@@ -23,19 +24,15 @@ fn solve(state: &LFState, days: u32) -> u64 {
 }
 
 impl Day for Day06 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut nums = String::new();
-        reader.read_line(&mut nums)?;
-
-        let nums = nums
-            .trim()
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<Vec<u8>, _>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let nums: Vec<usize> = util::read_csv(reader)?;
 
         let mut state: LFState = Default::default();
+        let max_timer = state.len() - 1;
         for x in nums {
-            state[x as usize] += 1;
+            *state.get_mut(x).ok_or_else(|| {
+                AocError::parse(format!("timer {} is larger than {}", x, max_timer)).at_line(1)
+            })? += 1;
         }
 
         Ok(Self { state })
@@ -55,13 +52,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day06.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day06::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "383160");
-        assert_eq!(day.part2(), "1721148811504");
+        answers::test_day::<Day06>(6);
     }
 }
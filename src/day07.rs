@@ -1,5 +1,6 @@
 use crate::day::Day;
-use std::error::Error;
+use crate::error::AocError;
+use crate::util;
 use std::io::BufRead;
 
 pub struct Day07 {
@@ -35,15 +36,8 @@ fn solve(positions: &[i64], dist: fn(i64) -> i64) -> u64 {
 }
 
 impl Day for Day07 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut positions = String::new();
-        reader.read_line(&mut positions)?;
-
-        let positions = positions
-            .trim()
-            .split(',')
-            .map(str::parse)
-            .collect::<Result<Vec<i64>, _>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let positions: Vec<i64> = util::read_csv(reader)?;
 
         // TODO: sort positions here if that matters
         Ok(Self { positions })
@@ -63,13 +57,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day07.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day07::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "347011");
-        assert_eq!(day.part2(), "98363777");
+        answers::test_day::<Day07>(7);
     }
 }
@@ -1,20 +1,9 @@
 use crate::day::Day;
-use std::error::Error;
-use std::fmt;
-use std::io::{self, BufRead};
+use crate::error::AocError;
+use crate::util;
+use std::io::BufRead;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
-struct ParsePatternError(String);
-
-impl fmt::Display for ParsePatternError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
-    }
-}
-
-impl Error for ParsePatternError {}
-
 #[derive(Debug)]
 struct Pattern {
     clues: Vec<u8>,
@@ -22,24 +11,52 @@ struct Pattern {
 }
 
 impl FromStr for Pattern {
-    type Err = ParsePatternError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        fn to_byte(s: &str) -> u8 {
-            s.as_bytes()
-                .iter()
-                .fold(0, |acc, c| acc | (1 << (c - b'a')))
+        fn to_byte(s: &str) -> Result<u8, AocError> {
+            s.bytes().try_fold(0, |acc, c| match c {
+                b'a'..=b'g' => Ok(acc | (1 << (c - b'a'))),
+                _ => Err(AocError::parse(format!(
+                    "expected segments a through g, found {:?}",
+                    char::from(c)
+                ))),
+            })
         }
 
-        let parts: Vec<&str> = s.split(" | ").collect();
-        if parts.len() != 2 {
-            return Err(ParsePatternError(s.to_string()));
-        }
-        let clues: Vec<u8> = parts[0].split_whitespace().map(to_byte).collect();
-        let output: Vec<u8> = parts[1].split_whitespace().map(to_byte).collect();
+        let (clues, output) = s
+            .split_once(" | ")
+            .ok_or_else(|| AocError::parse("expected ` | ` between clues and output"))?;
+        let clues: Vec<u8> = clues
+            .split_whitespace()
+            .map(to_byte)
+            .collect::<Result<_, _>>()?;
+        let output: Vec<u8> = output
+            .split_whitespace()
+            .map(to_byte)
+            .collect::<Result<_, _>>()?;
 
         if clues.len() != 10 || output.len() != 4 {
-            return Err(ParsePatternError(s.to_string()));
+            return Err(AocError::parse(format!(
+                "expected 10 clues and 4 outputs, found {} and {}",
+                clues.len(),
+                output.len()
+            )));
+        }
+
+        // Decoding leans on the 1 and the 4, the only digits with 2 and 4 segments
+        for segs in [2, 4] {
+            if clues
+                .iter()
+                .filter(|clue| clue.count_ones() == segs)
+                .count()
+                != 1
+            {
+                return Err(AocError::parse(format!(
+                    "expected exactly one clue with {} segments",
+                    segs
+                )));
+            }
         }
 
         Ok(Self { clues, output })
@@ -110,11 +127,8 @@ pub struct Day08 {
 }
 
 impl Day for Day08 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let patterns: Vec<Pattern> = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<Vec<Pattern>, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let patterns: Vec<Pattern> = util::parse_lines(reader)?;
 
         Ok(Self { patterns })
     }
@@ -147,13 +161,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day08.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day08::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "476");
-        assert_eq!(day.part2(), "1011823");
+        answers::test_day::<Day08>(8);
     }
 }
@@ -1,116 +1,29 @@
 use crate::day::Day;
-use crate::util::{Grid, GridRef};
+use crate::error::AocError;
+use crate::util::union_find::UnionFind;
+use crate::util::{self, Grid};
 
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
-struct UnionFind {
-    // parent[x] is the index if >= 0 and negative size if < 0
-    parent: Vec<i64>,
+fn low_points(grid: &Grid<u8>) -> Vec<u8> {
+    grid.iter()
+        .filter(|&(pos, &height)| grid.neighbors4(pos).all(|nb| height < grid[nb]))
+        .map(|(_, &height)| height)
+        .collect()
 }
 
-impl UnionFind {
-    pub fn new(n: usize) -> Self {
-        Self {
-            parent: vec![-1; n],
-        }
-    }
-
-    pub fn find(&mut self, idx: usize) -> usize {
-        if self.parent[idx] < 0 {
-            idx
-        } else {
-            // Path compression
-            let res = self.find(self.parent[idx] as usize);
-            self.parent[idx] = res as i64;
-
-            res
-        }
-    }
-
-    pub fn merge(&mut self, u: usize, v: usize) -> bool {
-        let ur = self.find(u);
-        let vr = self.find(v);
-        if ur != vr {
-            self.parent[ur] += self.parent[vr];
-            self.parent[vr] = ur as i64;
-        }
-
-        ur != vr
-    }
-
-    pub fn get_sizes(&self) -> Vec<i64> {
-        self.parent
-            .iter()
-            .filter_map(|&val| if val < 0 { Some(-val) } else { None })
-            .collect()
-    }
-}
-
-fn low_points(grid: GridRef) -> Vec<u8> {
-    let n = grid.len();
-    let m = grid[0].len();
-    assert!(grid.iter().all(|row| row.len() == m));
-
-    let neighbors = |r, c| {
-        let mut neighbors = vec![];
-        if r > 0 {
-            neighbors.push((r - 1, c));
-        }
-        if c > 0 {
-            neighbors.push((r, c - 1));
-        }
-        if r + 1 < n {
-            neighbors.push((r + 1, c));
-        }
-        if c + 1 < m {
-            neighbors.push((r, c + 1));
-        }
-
-        neighbors
-    };
-
-    let mut res = vec![];
-    for row_idx in 0..n {
-        for col_idx in 0..m {
-            let mut is_low = true;
-            for (neighbor_row, neighbor_col) in neighbors(row_idx, col_idx) {
-                is_low &= grid[row_idx][col_idx] < grid[neighbor_row][neighbor_col];
-            }
-
-            if is_low {
-                res.push(grid[row_idx][col_idx]);
-            }
-        }
-    }
-
-    res
-}
-
-fn components(grid: GridRef) -> Vec<i64> {
+fn components(grid: &Grid<u8>) -> Vec<i64> {
     // TODO: This method returns each cell of size 9 as a size-1 CC, which may not be great...
-    let n = grid.len();
-    let m = grid[0].len();
-    assert!(grid.iter().all(|row| row.len() == m));
-
-    let get_idx = |row_idx, col_idx| m * row_idx + col_idx;
-
-    let mut uf = UnionFind::new(n * m);
+    let mut uf = UnionFind::new(grid.len());
 
-    for row_idx in 0..n {
-        for col_idx in 0..m {
-            if grid[row_idx][col_idx] == 9 {
-                continue;
-            }
+    for (pos, &height) in grid.iter() {
+        if height == 9 {
+            continue;
+        }
 
-            let cur_idx = get_idx(row_idx, col_idx);
-            if row_idx + 1 < n && grid[row_idx + 1][col_idx] != 9 {
-                let next_idx = get_idx(row_idx + 1, col_idx);
-                uf.merge(cur_idx, next_idx);
-            }
-            if col_idx + 1 < m && grid[row_idx][col_idx + 1] != 9 {
-                let next_idx = get_idx(row_idx, col_idx + 1);
-                uf.merge(cur_idx, next_idx);
+        for nb in grid.neighbors4(pos) {
+            if grid[nb] != 9 {
+                uf.merge(grid.flat_index(pos), grid.flat_index(nb));
             }
         }
     }
@@ -119,17 +32,12 @@ fn components(grid: GridRef) -> Vec<i64> {
 }
 
 pub struct Day09 {
-    grid: Grid,
+    grid: Grid<u8>,
 }
 
 impl Day for Day09 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let grid: Grid = reader
-            .lines()
-            .map(|line_res| {
-                line_res.map(|line| line.bytes().map(|c| c - b'0').collect::<Vec<u8>>())
-            })
-            .collect::<io::Result<_>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let grid = util::read_grid(reader)?;
 
         Ok(Self { grid })
     }
@@ -151,13 +59,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day09.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day09::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "500");
-        assert_eq!(day.part2(), "970200");
+        answers::test_day::<Day09>(9);
     }
 }
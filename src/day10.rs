@@ -1,5 +1,5 @@
 use crate::day::Day;
-use std::error::Error;
+use crate::error::AocError;
 use std::io::{self, BufRead};
 
 const fn is_open(token: u8) -> bool {
@@ -70,9 +70,22 @@ pub struct Day10 {
 }
 
 impl Day for Day10 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
         let chunks: Vec<String> = reader.lines().collect::<io::Result<Vec<_>>>()?;
 
+        for (line_idx, chunk) in chunks.iter().enumerate() {
+            if let Some(col_idx) = chunk
+                .bytes()
+                .position(|c| !is_open(c) && match_close(c) == u8::MAX)
+            {
+                return Err(AocError::at(
+                    line_idx + 1,
+                    col_idx + 1,
+                    "expected a bracket",
+                ));
+            }
+        }
+
         Ok(Self { chunks })
     }
 
@@ -103,13 +116,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day10.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day10::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "399153");
-        assert_eq!(day.part2(), "2995077699");
+        answers::test_day::<Day10>(10);
     }
 }
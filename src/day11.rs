@@ -1,68 +1,42 @@
 use crate::day::Day;
-use crate::util::{Grid, GridRef};
+use crate::error::AocError;
+use crate::util::{self, Grid};
 
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
-fn step(grid: GridRef) -> (Grid, u32) {
-    let n = grid.len();
-    let m = grid[0].len();
-    assert!(grid.iter().all(|row| row.len() == m));
-
-    let in_bounds = |r, c| 0 <= r && r < (n as i64) && 0 <= c && c < (m as i64);
-
-    let mut res: Grid = grid
+fn step(grid: &Grid<u8>) -> (Grid<u8>, u32) {
+    let mut res = grid.map(|x| x + 1);
+    let mut stk: Vec<(usize, usize)> = res
         .iter()
-        .map(|row| row.iter().map(|x| x + 1).collect())
+        .filter(|&(_, &cell)| cell == 10)
+        .map(|(pos, _)| pos)
         .collect();
-    let mut stk = vec![];
     let mut flips = vec![];
-    for (row_idx, row) in res.iter().enumerate() {
-        for (col_idx, &cell) in row.iter().enumerate() {
-            if cell == 10 {
-                stk.push((row_idx, col_idx));
-            }
-        }
-    }
 
-    while !stk.is_empty() {
-        let (row_idx, col_idx) = stk.pop().unwrap();
-        flips.push((row_idx, col_idx));
-        for dr in -1_i64..=1 {
-            for dc in -1_i64..=1 {
-                let new_row = (row_idx as i64) + dr;
-                let new_col = (col_idx as i64) + dc;
-                if in_bounds(new_row, new_col) {
-                    let new_row = new_row as usize;
-                    let new_col = new_col as usize;
-                    res[new_row][new_col] += 1;
-                    if res[new_row][new_col] == 10 {
-                        stk.push((new_row, new_col));
-                    }
-                }
+    while let Some(pos) = stk.pop() {
+        flips.push(pos);
+        for nb in grid.neighbors8(pos) {
+            res[nb] += 1;
+            if res[nb] == 10 {
+                stk.push(nb);
             }
         }
     }
 
-    for &(row_idx, col_idx) in &flips {
-        res[row_idx][col_idx] = 0;
+    for &pos in &flips {
+        res[pos] = 0;
     }
 
     (res, flips.len() as u32)
 }
 
 pub struct Day11 {
-    grid: Grid,
+    grid: Grid<u8>,
 }
 
 impl Day for Day11 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let grid: Grid = reader
-            .lines()
-            .map(|line_res| {
-                line_res.map(|line| line.bytes().map(|c| c - b'0').collect::<Vec<u8>>())
-            })
-            .collect::<io::Result<_>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let grid = util::read_grid(reader)?;
 
         Ok(Self { grid })
     }
@@ -81,7 +55,7 @@ impl Day for Day11 {
         let mut grid = self.grid.clone();
         let mut last_flash = 0;
 
-        let all_cells = (grid.len() * grid[0].len()).try_into().unwrap();
+        let all_cells = grid.len().try_into().unwrap();
 
         while last_flash != all_cells {
             let (nxt_grid, flashes) = step(&grid);
@@ -99,13 +73,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day11.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day11::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "1637");
-        assert_eq!(day.part2(), "242");
+        answers::test_day::<Day11>(11);
     }
 }
@@ -1,124 +1,112 @@
 use crate::day::Day;
+use crate::error::AocError;
+use crate::util::graph::{Graph, NodeId};
 use std::collections::HashMap;
-use std::error::Error;
-use std::io::{self, BufRead};
-use std::iter;
+use std::io::BufRead;
 
 const START: &str = "start";
 const END: &str = "end";
 
+/// Counts paths from start to end. Small caves visited so far are a bitmask over node ids, so
+/// the number of ways to finish from a cave only depends on `(cave, visited, has_double)`.
 struct Solver<'a> {
-    freq: HashMap<&'a str, u32>,
-    edges: &'a [(String, String)],
-    has_double: bool,
-}
-
-fn is_lower(s: &str) -> bool {
-    s.bytes().all(|c| (b'a'..=b'z').contains(&c))
+    graph: &'a Graph,
+    start: NodeId,
+    end: NodeId,
+    memo: HashMap<(NodeId, u64, bool), u64>,
 }
 
 impl<'a> Solver<'a> {
-    fn new(edges: &'a [(String, String)], can_double: bool) -> Self {
+    fn new(graph: &'a Graph, start: NodeId, end: NodeId) -> Self {
         Self {
-            freq: iter::once((START, 1)).collect(),
-            edges,
-            has_double: !can_double,
+            graph,
+            start,
+            end,
+            memo: HashMap::new(),
         }
     }
 
-    fn freq_of(&self, node: &'a str) -> u32 {
-        *self.freq.get(node).unwrap_or(&0)
-    }
-
-    fn can_move(&self, node: &'a str) -> bool {
-        if node == START || node == END {
-            self.freq_of(node) == 0
-        } else if is_lower(node) {
-            let cur_freq = self.freq_of(node);
-            cur_freq == 0 || (!self.has_double && cur_freq == 1)
-        } else {
-            true
+    fn count(&mut self, node: NodeId, visited: u64, has_double: bool) -> u64 {
+        if node == self.end {
+            return 1;
         }
-    }
-
-    fn enter(&mut self, node: &'a str) {
-        let new_freq = self.freq_of(node) + 1;
 
-        self.freq.insert(node, new_freq);
-        if new_freq == 2 && is_lower(node) {
-            self.has_double = true;
+        if let Some(&res) = self.memo.get(&(node, visited, has_double)) {
+            return res;
         }
-    }
 
-    fn leave(&mut self, node: &'a str) {
-        let old_freq = self.freq_of(node);
+        let graph = self.graph;
+        let res = graph
+            .neighbors(node)
+            .iter()
+            .map(|&next| {
+                let bit = 1 << next;
+                if !graph.is_lower(next) {
+                    self.count(next, visited, has_double)
+                } else if visited & bit == 0 {
+                    self.count(next, visited | bit, has_double)
+                } else if !has_double && next != self.start {
+                    self.count(next, visited, true)
+                } else {
+                    0
+                }
+            })
+            .sum();
 
-        self.freq.insert(node, old_freq - 1);
-        if old_freq == 2 && is_lower(node) {
-            self.has_double = false;
-        }
-    }
+        self.memo.insert((node, visited, has_double), res);
 
-    fn dfs(&mut self, cur_node: &'a str) -> u32 {
-        if cur_node == END {
-            1
-        } else {
-            self.edges
-                .iter()
-                .filter_map(|(u, v)| {
-                    if u == cur_node {
-                        Some(v)
-                    } else if v == cur_node {
-                        Some(u)
-                    } else {
-                        None
-                    }
-                })
-                .map(|next_node| {
-                    if self.can_move(next_node) {
-                        self.enter(next_node);
-                        let res = self.dfs(next_node);
-                        self.leave(next_node);
-
-                        res
-                    } else {
-                        0
-                    }
-                })
-                .sum()
-        }
+        res
     }
 }
 
 pub struct Day12 {
-    edges: Vec<(String, String)>,
+    graph: Graph,
+    start: NodeId,
+    end: NodeId,
 }
 
 impl Day12 {
-    fn count_paths(&self, can_double: bool) -> u32 {
-        let mut solver = Solver::new(&self.edges, can_double);
+    fn count_paths(&self, can_double: bool) -> u64 {
+        let mut solver = Solver::new(&self.graph, self.start, self.end);
 
-        solver.dfs(START)
+        solver.count(self.start, 1 << self.start, !can_double)
     }
 }
 
 impl Day for Day12 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let edges: Vec<(String, String)> = reader
-            .lines()
-            .map(|line_res| {
-                line_res.and_then(|line| {
-                    let parts: Vec<&str> = line.split('-').collect();
-                    if parts.len() == 2 {
-                        Ok((parts[0].to_string(), parts[1].to_string()))
-                    } else {
-                        Err(io::Error::from(io::ErrorKind::InvalidInput))
-                    }
-                })
-            })
-            .collect::<io::Result<Vec<_>>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+
+        let graph: Graph = input.parse()?;
+
+        let [start, end] = [START, END].map(|name| {
+            graph
+                .id(name)
+                .ok_or_else(|| AocError::parse(format!("no `{}` cave", name)))
+        });
+        let (start, end) = (start?, end?);
+
+        if graph.len() > 64 {
+            return Err(AocError::parse(format!(
+                "at most 64 caves are supported, found {}",
+                graph.len()
+            )));
+        }
+
+        // Two connected big caves would allow infinitely many paths
+        if let Some((u, v)) = graph
+            .edges()
+            .find(|&(u, v)| !graph.is_lower(u) && !graph.is_lower(v))
+        {
+            return Err(AocError::parse(format!(
+                "big caves {} and {} are connected",
+                graph.name(u),
+                graph.name(v)
+            )));
+        }
 
-        Ok(Self { edges })
+        Ok(Self { graph, start, end })
     }
 
     fn part1(&self) -> String {
@@ -135,13 +123,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day12.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day12::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "3802");
-        assert_eq!(day.part2(), "99448");
+        answers::test_day::<Day12>(12);
     }
 }
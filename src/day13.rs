@@ -1,10 +1,10 @@
 use crate::day::Day;
+use crate::error::{at_line, AocError};
 use crate::util::Point;
 
 use std::cmp;
 use std::collections::HashSet;
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Axis {
@@ -18,8 +18,8 @@ where
 {
     points
         .map(|pt| match (fold_axis, pt.x > fold_val, pt.y > fold_val) {
-            (Axis::X, true, _) => Point::new(fold_val - (pt.x - fold_val), pt.y),
-            (Axis::Y, _, true) => Point::new(pt.x, fold_val - (pt.y - fold_val)),
+            (Axis::X, true, _) => Point::new(2 * fold_val - pt.x, pt.y),
+            (Axis::Y, _, true) => Point::new(pt.x, 2 * fold_val - pt.y),
             _ => pt,
         })
         .collect()
@@ -59,32 +59,48 @@ pub struct Day13 {
 }
 
 impl Day for Day13 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut line_iter = reader.lines();
-        let points: HashSet<Point> = line_iter
-            .by_ref()
-            .take_while(|line_res| !line_res.as_ref().unwrap().is_empty())
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<_, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        const FOLD_PREFIX: &str = "fold along ";
+
+        let mut line_iter = reader.lines().enumerate();
+        let mut points = HashSet::new();
+        for (line_idx, line) in line_iter.by_ref() {
+            let line = line?;
+            if line.is_empty() {
+                break;
+            }
+
+            points.insert(line.parse::<Point>().map_err(at_line(line_idx + 1))?);
+        }
 
         let folds: Vec<(Axis, i64)> = line_iter
-            .map(|line_res| {
-                line_res.map(|line| {
-                    let fold_data = line.split(' ').last().unwrap();
-                    let fold_data: Vec<_> = fold_data.split('=').collect();
-
-                    let axis = match fold_data.get(0) {
-                        Some(&"x") => Axis::X,
-                        Some(&"y") => Axis::Y,
-                        _ => unreachable!(),
-                    };
-
-                    let fold_point = fold_data[1].parse();
-
-                    fold_point.map(|pt| (axis, pt))
-                })
+            .map(|(line_idx, line)| {
+                let line = line?;
+                let fold_data = line.strip_prefix(FOLD_PREFIX).ok_or_else(|| {
+                    AocError::parse(format!("expected `{}<axis>=<value>`", FOLD_PREFIX))
+                        .at_line(line_idx + 1)
+                })?;
+
+                let column = FOLD_PREFIX.len() + 1;
+                let axis = match fold_data.split_once('=') {
+                    Some(("x", _)) => Axis::X,
+                    Some(("y", _)) => Axis::Y,
+                    _ => return Err(AocError::at(line_idx + 1, column, "expected x= or y=")),
+                };
+
+                let fold_point = fold_data[2..].parse().map_err(|e| {
+                    AocError::from(e)
+                        .at_line(line_idx + 1)
+                        .at_column(column + 2)
+                })?;
+
+                Ok((axis, fold_point))
             })
-            .collect::<io::Result<Result<_, _>>>()??;
+            .collect::<Result<_, _>>()?;
+
+        if folds.is_empty() {
+            return Err(AocError::parse("expected at least one fold"));
+        }
 
         Ok(Self { points, folds })
     }
@@ -116,22 +132,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        const ANS: &'static str = concat!(
-            ".##..###..#..#...##.####.###...##...##.\n",
-            "#..#.#..#.#.#.....#.#....#..#.#..#.#..#\n",
-            "#..#.###..##......#.###..###..#....#...\n",
-            "####.#..#.#.#.....#.#....#..#.#.##.#...\n",
-            "#..#.#..#.#.#..#..#.#....#..#.#..#.#..#\n",
-            "#..#.###..#..#..##..#....###...###..##.\n",
-        );
-
-        let data = include_bytes!("../data_files/day13.txt");
-        let mut reader = BufReader::new(&data[..]);
-
-        let day = Day13::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "745");
-        assert_eq!(day.part2(), ANS);
+
+        answers::test_day::<Day13>(13);
     }
 }
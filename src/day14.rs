@@ -1,11 +1,10 @@
 use crate::day::Day;
-use crate::util::ParseInputError;
+use crate::error::{at_line, AocError};
 
 use std::cmp;
 use std::collections::HashMap;
-use std::error::Error;
 use std::hash::Hash;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
 fn add_map<K>(hash_map: &mut HashMap<K, u64>, key: K, inc: u64)
 where
@@ -67,26 +66,30 @@ impl Day14 {
 }
 
 impl Day for Day14 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        fn parse_line(s: String) -> Result<((u8, u8), u8), ParseInputError> {
-            let bytes = s.as_bytes();
-
-            if bytes.len() == 7 {
-                Ok(((bytes[0], bytes[1]), bytes[6]))
-            } else {
-                Err(ParseInputError(s))
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        fn parse_line(s: String) -> Result<((u8, u8), u8), AocError> {
+            match s.as_bytes() {
+                &[a, b, b' ', b'-', b'>', b' ', c] => Ok(((a, b), c)),
+                _ => Err(AocError::parse(format!(
+                    "expected `AB -> C`, found {:?}",
+                    s
+                ))),
             }
         }
 
         let mut template = String::new();
         reader.read_line(&mut template)?;
-        let template = template.trim().bytes().collect();
+        let template: Vec<u8> = template.trim().bytes().collect();
+        if template.is_empty() {
+            return Err(AocError::parse("expected a polymer template").at_line(1));
+        }
 
         let rules: HashMap<(u8, u8), u8> = reader
             .lines()
+            .enumerate()
             .skip(1) // Skip the empty line between template and rules
-            .map(|line_res| line_res.map(parse_line))
-            .collect::<io::Result<Result<_, _>>>()??;
+            .map(|(line_idx, line)| parse_line(line?).map_err(at_line(line_idx + 2)))
+            .collect::<Result<_, _>>()?;
 
         Ok(Self { template, rules })
     }
@@ -105,13 +108,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day14.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day14::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "2712");
-        assert_eq!(day.part2(), "8336623059567");
+        answers::test_day::<Day14>(14);
     }
 }
@@ -1,23 +1,25 @@
 use crate::day::Day;
+use crate::error::AocError;
 
-use std::error::Error;
 use std::io::BufRead;
+use std::str::FromStr;
 
+/// A BITS packet, parsed from its hex transmission with `str::parse`
 #[derive(Debug)]
-struct Packet {
-    version: u8,
-    type_id: u8,
-    data: Data,
+pub struct Packet {
+    pub version: u8,
+    pub type_id: u8,
+    pub data: Data,
 }
 
 #[derive(Debug)]
-enum Data {
+pub enum Data {
     Literal(i64),
     Operator(Vec<Packet>),
 }
 
 impl Packet {
-    fn evaluate(&self) -> i64 {
+    pub fn evaluate(&self) -> i64 {
         match &self.data {
             Data::Literal(x) => *x,
             Data::Operator(children) => match &self.type_id {
@@ -61,85 +63,109 @@ fn add_bit_i64(v: i64, b: u8) -> i64 {
     (v << 1) | i64::from(b)
 }
 
-fn parse_literal(i: &[u8], acc: i64) -> (&[u8], i64) {
-    let chunk = &i[0..5];
-    let rem = &i[5..];
+/// Split off the first `n` bits, failing if the transmission is cut short
+fn take(i: &[u8], n: usize) -> Result<(&[u8], &[u8]), AocError> {
+    if i.len() < n {
+        Err(AocError::parse(
+            "transmission ends in the middle of a packet",
+        ))
+    } else {
+        Ok(i.split_at(n))
+    }
+}
+
+fn parse_literal(i: &[u8], acc: i64) -> Result<(&[u8], i64), AocError> {
+    let (chunk, rem) = take(i, 5)?;
     let val = chunk[1..].iter().copied().fold(acc, add_bit_i64);
 
     if chunk[0] > 0 {
         parse_literal(rem, val)
     } else {
-        (rem, val)
+        Ok((rem, val))
     }
 }
 
 // TODO: nom?
-fn parse_packet(i: &[u8]) -> (&[u8], Packet) {
-    let version = i[0..3].iter().copied().fold(0, add_bit_u8);
-    let type_id = i[3..6].iter().copied().fold(0, add_bit_u8);
-
-    let mut i = &i[6..];
+fn parse_packet(i: &[u8]) -> Result<(&[u8], Packet), AocError> {
+    let (version, i) = take(i, 3)?;
+    let version = version.iter().copied().fold(0, add_bit_u8);
+    let (type_id, mut i) = take(i, 3)?;
+    let type_id = type_id.iter().copied().fold(0, add_bit_u8);
 
     let data = if type_id == 4 {
-        let (rem, value) = parse_literal(i, 0);
+        let (rem, value) = parse_literal(i, 0)?;
         i = rem;
         Data::Literal(value)
     } else {
-        let len_type_id = i[0];
-        i = &i[1..];
-
-        let children = match len_type_id {
-            0 => {
-                let packet_len = i[..15].iter().copied().fold(0, add_bit_i64) as usize;
-                i = &i[15..];
-
-                let mut children = vec![];
-                let mut child_slice = &i[..packet_len];
-                i = &i[packet_len..];
-
-                while !child_slice.is_empty() {
-                    let (rem, packet) = parse_packet(child_slice);
-                    child_slice = rem;
-                    children.push(packet);
-                }
+        let (len_type_id, rem) = take(i, 1)?;
+        i = rem;
+
+        let children = if len_type_id[0] == 0 {
+            let (packet_len, rem) = take(i, 15)?;
+            let packet_len = packet_len.iter().copied().fold(0, add_bit_i64) as usize;
+
+            let mut children = vec![];
+            let (mut child_slice, rem) = take(rem, packet_len)?;
+            i = rem;
 
-                children
+            while !child_slice.is_empty() {
+                let (rem, packet) = parse_packet(child_slice)?;
+                child_slice = rem;
+                children.push(packet);
             }
-            1 => {
-                let num_children = i[..11].iter().copied().fold(0, add_bit_i64);
-                i = &i[11..];
-                let mut children = vec![];
-                for _ in 0..num_children {
-                    let (rem, packet) = parse_packet(i);
-                    i = rem;
-                    children.push(packet);
-                }
 
-                children
+            children
+        } else {
+            let (num_children, rem) = take(i, 11)?;
+            let num_children = num_children.iter().copied().fold(0, add_bit_i64);
+            i = rem;
+
+            let mut children = vec![];
+            for _ in 0..num_children {
+                let (rem, packet) = parse_packet(i)?;
+                i = rem;
+                children.push(packet);
             }
-            _ => unimplemented!(),
+
+            children
         };
 
+        // Comparisons need exactly two operands, everything else at least one
+        let arity_ok = match type_id {
+            5..=7 => children.len() == 2,
+            _ => !children.is_empty(),
+        };
+        if !arity_ok {
+            return Err(AocError::parse(format!(
+                "operator of type {} can't have {} sub-packets",
+                type_id,
+                children.len()
+            )));
+        }
+
         Data::Operator(children)
     };
 
-    (
+    Ok((
         i,
         Packet {
             version,
             type_id,
             data,
         },
-    )
+    ))
 }
 
 #[allow(clippy::identity_op)]
-fn parse_data(s: &str) -> Packet {
+fn parse_data(s: &str) -> Result<Packet, AocError> {
     let bits: Vec<u8> = s
         .bytes()
-        .flat_map(|c| {
-            let x = if c <= b'9' { c - b'0' } else { c + 10 - b'A' };
-            assert!(x < 0x10);
+        .enumerate()
+        .map(|(idx, c)| {
+            let x = char::from(c)
+                .to_digit(16)
+                .ok_or_else(|| AocError::at(1, idx + 1, "expected a hex digit"))?
+                as u8;
             let bits = [
                 (x & 0x8) >> 3,
                 (x & 0x4) >> 2,
@@ -147,21 +173,35 @@ fn parse_data(s: &str) -> Packet {
                 (x & 0x1) >> 0,
             ];
 
-            bits.into_iter()
+            Ok(bits)
         })
+        .collect::<Result<Vec<_>, AocError>>()?
+        .into_iter()
+        .flatten()
         .collect();
 
-    let (_, packet) = parse_packet(&bits);
+    let (_, packet) = parse_packet(&bits).map_err(|e| e.at_line(1))?;
 
-    packet
+    Ok(packet)
 }
 
-fn version_sum(packet: &Packet) -> i64 {
-    i64::from(packet.version)
-        + match &packet.data {
-            Data::Literal(_) => 0,
-            Data::Operator(children) => children.iter().map(version_sum).sum(),
-        }
+impl Packet {
+    /// Sum of the versions of this packet and every packet nested in it
+    pub fn version_sum(&self) -> i64 {
+        i64::from(self.version)
+            + match &self.data {
+                Data::Literal(_) => 0,
+                Data::Operator(children) => children.iter().map(Packet::version_sum).sum(),
+            }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_data(s)
+    }
 }
 
 pub struct Day16 {
@@ -169,17 +209,17 @@ pub struct Day16 {
 }
 
 impl Day for Day16 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
         let mut command = String::new();
         reader.read_line(&mut command)?;
 
-        let packet = parse_data(command.trim());
+        let packet = command.trim().parse()?;
 
         Ok(Self { packet })
     }
 
     fn part1(&self) -> String {
-        version_sum(&self.packet).to_string()
+        self.packet.version_sum().to_string()
     }
 
     fn part2(&self) -> String {
@@ -192,13 +232,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day16.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day16::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "871");
-        assert_eq!(day.part2(), "68703010504");
+        answers::test_day::<Day16>(16);
     }
 }
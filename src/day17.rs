@@ -1,8 +1,8 @@
 use crate::day::Day;
+use crate::error::{at_line, AocError};
 use crate::util::Point;
 
 use std::cmp;
-use std::error::Error;
 use std::io::BufRead;
 
 struct Position {
@@ -18,8 +18,9 @@ impl Position {
     fn step(&self) -> Self {
         assert!(self.vel.x >= 0);
         Self {
-            pos: Point::new(self.pos.x + self.vel.x, self.pos.y + self.vel.y),
-            vel: Point::new(cmp::max(0, self.vel.x - 1), self.vel.y - 1),
+            pos: self.pos + self.vel,
+            // Drag pulls x towards 0, gravity pulls y down
+            vel: self.vel + Point::new(-self.vel.x.signum(), -1),
         }
     }
 }
@@ -39,7 +40,7 @@ impl Day17 {
     }
 
     fn run_with_vel(&self, vel: Point) -> (bool, i64) {
-        let mut pos = Position::new(Point::new(0, 0), vel);
+        let mut pos = Position::new(Point::ORIGIN, vel);
 
         let mut hit_goal = false;
         let mut max_height = 0;
@@ -72,25 +73,43 @@ impl Day17 {
 }
 
 impl Day for Day17 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        fn parse_range(s: &str) -> (i64, i64) {
-            let coords: Vec<i64> = s[2..].split("..").map(|v| v.parse().unwrap()).collect();
-
-            (coords[0], coords[1])
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        fn parse_range(s: &str, axis: char) -> Result<(i64, i64), AocError> {
+            let (lo, hi) = s
+                .strip_prefix(axis)
+                .and_then(|s| s.strip_prefix('='))
+                .and_then(|s| s.split_once(".."))
+                .ok_or_else(|| {
+                    AocError::parse(format!("expected `{}=lo..hi`, found {:?}", axis, s))
+                })?;
+
+            Ok((lo.parse()?, hi.parse()?))
         }
 
         let mut bounds_desc = String::new();
         reader.read_line(&mut bounds_desc)?;
 
-        let bounds_desc: Vec<&str> = bounds_desc.trim().split(' ').collect();
+        let (x_desc, y_desc) = bounds_desc
+            .trim()
+            .strip_prefix("target area: ")
+            .and_then(|desc| desc.split_once(", "))
+            .ok_or_else(|| {
+                AocError::parse("expected `target area: x=lo..hi, y=lo..hi`").at_line(1)
+            })?;
 
-        let x_bounds = parse_range(bounds_desc[2].trim_end_matches(','));
-        let y_bounds = parse_range(bounds_desc[3]);
+        let x_bounds = parse_range(x_desc, 'x').map_err(at_line(1))?;
+        let y_bounds = parse_range(y_desc, 'y').map_err(at_line(1))?;
 
-        assert!(x_bounds.0 < x_bounds.1);
-        assert!(y_bounds.0 < y_bounds.1);
-        assert!(0 <= x_bounds.0);
-        assert!(y_bounds.1 < 0);
+        if x_bounds.0 > x_bounds.1 || y_bounds.0 > y_bounds.1 {
+            return Err(AocError::parse("empty target area").at_line(1));
+        }
+
+        if x_bounds.0 < 0 || y_bounds.1 >= 0 {
+            return Err(AocError::parse(
+                "only target areas to the right of and below the probe are supported",
+            )
+            .at_line(1));
+        }
 
         Ok(Self { x_bounds, y_bounds })
     }
@@ -109,13 +128,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day17.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day17::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "6786");
-        assert_eq!(day.part2(), "2313");
+        answers::test_day::<Day17>(17);
     }
 }
@@ -1,14 +1,15 @@
 use crate::day::Day;
-use crate::util::ParseInputError;
+use crate::error::AocError;
+use crate::util;
 
-use std::error::Error;
 use std::fmt;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::ops;
 use std::str::FromStr;
 
+/// A snailfish number. Adding two of them reduces the result.
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Snailfish {
+pub enum Snailfish {
     Leaf(i64),
     Pair(Box<Snailfish>, Box<Snailfish>),
 }
@@ -25,34 +26,37 @@ impl Snailfish {
         }
     }
 
-    fn parse_str(s: &[u8]) -> Result<(&[u8], Self), ParseInputError> {
-        if s[0] == b'[' {
-            let (s, lhs) = Self::parse_str(&s[1..])?;
+    /// Parse one number off the front of `s`. `len` is the length of the whole string, used to
+    /// report error columns.
+    fn parse_str(s: &[u8], len: usize) -> Result<(&[u8], Self), AocError> {
+        let err = |s: &[u8], msg: &str| AocError::parse(msg).at_column(len - s.len() + 1);
 
-            if s[0] != b',' {
-                return Err(ParseInputError(String::from_utf8_lossy(s).into_owned()));
-            }
+        match s.first() {
+            Some(b'[') => {
+                let (s, lhs) = Self::parse_str(&s[1..], len)?;
+                let s = s.strip_prefix(b",").ok_or_else(|| err(s, "expected ','"))?;
 
-            let (s, rhs) = Self::parse_str(&s[1..])?;
-            if s[0] != b']' {
-                return Err(ParseInputError(String::from_utf8_lossy(s).into_owned()));
-            }
+                let (s, rhs) = Self::parse_str(s, len)?;
+                let s = s.strip_prefix(b"]").ok_or_else(|| err(s, "expected ']'"))?;
 
-            Ok((&s[1..], Snailfish::Pair(Box::new(lhs), Box::new(rhs))))
-        } else {
-            let mut k = 0;
-            while s[k].is_ascii_digit() {
-                k += 1;
+                Ok((s, Snailfish::make_pair(lhs, rhs)))
             }
-
-            let val = s[..k]
-                .iter()
-                .fold(0, |acc, x| 10 * acc + i64::from(x - b'0'));
-            Ok((&s[k..], Snailfish::Leaf(val)))
+            Some(c) if c.is_ascii_digit() => {
+                let k = s
+                    .iter()
+                    .position(|c| !c.is_ascii_digit())
+                    .unwrap_or(s.len());
+
+                let val = s[..k]
+                    .iter()
+                    .fold(0, |acc, x| 10 * acc + i64::from(x - b'0'));
+                Ok((&s[k..], Snailfish::Leaf(val)))
+            }
+            _ => Err(err(s, "expected '[' or a digit")),
         }
     }
 
-    fn magnitude(&self) -> i64 {
+    pub fn magnitude(&self) -> i64 {
         match self {
             Snailfish::Leaf(val) => *val,
             Snailfish::Pair(lhs, rhs) => 3 * lhs.magnitude() + 2 * rhs.magnitude(),
@@ -187,10 +191,14 @@ impl ops::Add for Snailfish {
 }
 
 impl FromStr for Snailfish {
-    type Err = ParseInputError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (_, snailfish) = Self::parse_str(s.as_bytes())?;
+        let (rest, snailfish) = Self::parse_str(s.as_bytes(), s.len())?;
+        if !rest.is_empty() {
+            return Err(AocError::parse("unexpected characters after number")
+                .at_column(s.len() - rest.len() + 1));
+        }
 
         Ok(snailfish)
     }
@@ -201,11 +209,12 @@ pub struct Day18 {
 }
 
 impl Day for Day18 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let snailfish: Vec<Snailfish> = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<_, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let snailfish: Vec<Snailfish> = util::parse_lines(reader)?;
+
+        if snailfish.is_empty() {
+            return Err(AocError::parse("expected at least one snailfish number"));
+        }
 
         Ok(Self { snailfish })
     }
@@ -364,7 +373,7 @@ mod snailfish_tests {
         ];
 
         let mut sf: Snailfish = to_add[0].parse().unwrap();
-        for (to_add, exp) in to_add[1..].into_iter().zip(exps.into_iter()) {
+        for (to_add, exp) in to_add[1..].iter().zip(exps) {
             sf = sf + to_add.parse().unwrap();
             let exp = exp.parse().unwrap();
             assert_eq!(sf, exp);
@@ -431,13 +440,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day18.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day18::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "3981");
-        assert_eq!(day.part2(), "4687");
+        answers::test_day::<Day18>(18);
     }
 }
@@ -1,9 +1,10 @@
 use crate::day::Day;
-use crate::util::Point3D;
+use crate::error::{at_line, AocError};
+use crate::util::{self, Point3D, Rotation3};
 
+use std::cell::OnceCell;
 use std::collections::{HashMap, HashSet};
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
 /// Assume the points in src are centered at the origin and using a canonical rep
 /// Find a center and orientation for the points in "other" that leads to at least 12 matches.
@@ -11,54 +12,27 @@ fn match_scanners(
     scanner: &HashSet<Point3D>,
     other: &[Point3D],
 ) -> Option<(Point3D, Vec<Point3D>)> {
-    // Mapping from one point to another with a fixed permutation + axis negation + center is an invertible
-    // linear transform. Therefore we can just compute all possible transforms and just return the
-    // one that works.
-
-    // Note that this implementation checks 48 orientations (3! axis orderings, and then 2^3 axis
-    // flips), but it should only need to check 24 because of the right hand rule. We'll leave that
-    // as a future optimization.
-
-    // TODO: Don't hard code these
-    const PERMS: [[usize; 3]; 6] = [
-        [0, 1, 2],
-        [0, 2, 1],
-        [1, 0, 2],
-        [1, 2, 0],
-        [2, 0, 1],
-        [2, 1, 0],
-    ];
-    const FLIPS: [[i64; 3]; 8] = [
-        [1, 1, 1],
-        [1, 1, -1],
-        [1, -1, 1],
-        [1, -1, -1],
-        [-1, 1, 1],
-        [-1, 1, -1],
-        [-1, -1, 1],
-        [-1, -1, -1],
-    ];
-
-    for perm in PERMS {
-        for flip in FLIPS {
-            let fixed: Vec<Point3D> = other.iter().map(|p| p.transform(perm, flip)).collect();
-            let mut freq = HashMap::new();
-            for &source in scanner {
-                for dest in &fixed {
-                    let center = source - *dest;
-
-                    freq.insert(center, freq.get(&center).unwrap_or(&0) + 1);
-                }
+    // Mapping from one point to another with a fixed rotation + center is an invertible linear
+    // transform. Therefore we can just compute all possible transforms and just return the one
+    // that works. Only the 24 proper rotations are checked, scanners can't see mirrored beacons.
+    for rot in Rotation3::all() {
+        let fixed: Vec<Point3D> = other.iter().map(|&p| rot.apply(p)).collect();
+        let mut freq = HashMap::new();
+        for &source in scanner {
+            for dest in &fixed {
+                let center = source - *dest;
+
+                freq.insert(center, freq.get(&center).unwrap_or(&0) + 1);
             }
+        }
 
-            let found = freq.iter().max_by(|(_, v1), (_, v2)| v1.cmp(v2));
+        let found = freq.iter().max_by(|(_, v1), (_, v2)| v1.cmp(v2));
 
-            if let Some((&center, &freq)) = found {
-                if freq >= 12 {
-                    let shifted: Vec<Point3D> = fixed.iter().map(|p| *p + center).collect();
+        if let Some((&center, &freq)) = found {
+            if freq >= 12 {
+                let shifted: Vec<Point3D> = fixed.iter().map(|p| *p + center).collect();
 
-                    return Some((center, shifted));
-                }
+                return Some((center, shifted));
             }
         }
     }
@@ -66,21 +40,38 @@ fn match_scanners(
     None
 }
 
-pub struct Day19 {
+/// Where every scanner sits and the beacons it sees, relative to scanner 0
+struct Layout {
     centers: Vec<Point3D>,
     fixed_points: Vec<HashSet<Point3D>>,
 }
 
+pub struct Day19 {
+    scanners: Vec<Vec<Point3D>>,
+    // Matching up the scanners is the slow part, so only do it once a part asks for it. Holds
+    // the index of a scanner that couldn't be placed on failure.
+    layout: OnceCell<Result<Layout, usize>>,
+}
+
 impl Day19 {
-    fn build(scanners: &[Vec<Point3D>]) -> Self {
-        let (centers, fixed_points) = Self::solve(scanners);
-        Self {
+    fn build(scanners: &[Vec<Point3D>]) -> Result<Layout, usize> {
+        let (centers, fixed_points) = Self::solve(scanners)?;
+        Ok(Layout {
             centers,
             fixed_points,
-        }
+        })
     }
 
-    fn solve(scanners: &[Vec<Point3D>]) -> (Vec<Point3D>, Vec<HashSet<Point3D>>) {
+    fn layout(&self) -> Result<&Layout, AocError> {
+        self.layout
+            .get_or_init(|| Self::build(&self.scanners))
+            .as_ref()
+            .map_err(|lost| {
+                AocError::parse(format!("scanner {} doesn't overlap with the others", lost))
+            })
+    }
+
+    fn solve(scanners: &[Vec<Point3D>]) -> Result<(Vec<Point3D>, Vec<HashSet<Point3D>>), usize> {
         let n = scanners.len();
         let mut vis = vec![false; n];
         let mut centers = vec![Point3D::new(0, 0, 0); n];
@@ -90,9 +81,7 @@ impl Day19 {
         fixed[0] = scanners[0].iter().copied().collect();
 
         let mut stk = vec![0];
-        while !stk.is_empty() {
-            let u = stk.pop().unwrap();
-
+        while let Some(u) = stk.pop() {
             // TODO: Figure out how to avoid this intermediate allocation
             let results: Vec<_> = (0..n)
                 .filter(|&v| !vis[v])
@@ -107,35 +96,67 @@ impl Day19 {
             }
         }
 
-        assert!(vis.iter().all(|&x| x));
-        (centers, fixed)
+        if let Some(lost) = vis.iter().position(|&x| !x) {
+            return Err(lost);
+        }
+
+        Ok((centers, fixed))
     }
 }
 
 impl Day for Day19 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let lines: Vec<String> = reader.lines().collect::<io::Result<_>>()?;
-        let scanners: Vec<Vec<Point3D>> = lines
-            .split(String::is_empty)
-            .map(|scanner| {
-                scanner[1..]
-                    .iter()
-                    .map(|pt: &String| pt.parse::<Point3D>())
-                    .collect::<Result<_, _>>()
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        // Each scanner is a `--- scanner N ---` header followed by its beacons
+        let scanners: Vec<Vec<Point3D>> = util::read_blocks(reader)?
+            .iter()
+            .map(|block| {
+                let mut lines = block.numbered();
+                match lines.next() {
+                    Some((_, header)) if header.starts_with("--- scanner") => {}
+                    _ => {
+                        return Err(AocError::parse("expected a `--- scanner N ---` header")
+                            .at_line(block.first_line))
+                    }
+                }
+
+                lines
+                    .map(|(line, beacon)| beacon.parse().map_err(at_line(line)))
+                    .collect()
             })
             .collect::<Result<_, _>>()?;
 
-        Ok(Self::build(&scanners))
+        if scanners.is_empty() {
+            return Err(AocError::parse("expected at least one scanner"));
+        }
+
+        Ok(Self {
+            scanners,
+            layout: OnceCell::new(),
+        })
     }
 
     fn part1(&self) -> String {
-        let all_points: HashSet<Point3D> = self.fixed_points.iter().flatten().copied().collect();
-
-        all_points.len().to_string()
+        self.try_part1().unwrap_or_else(|e| panic!("{}", e))
     }
 
     fn part2(&self) -> String {
-        let centers = &self.centers;
+        self.try_part2().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    fn try_part1(&self) -> Result<String, AocError> {
+        let all_points: HashSet<Point3D> = self
+            .layout()?
+            .fixed_points
+            .iter()
+            .flatten()
+            .copied()
+            .collect();
+
+        Ok(all_points.len().to_string())
+    }
+
+    fn try_part2(&self) -> Result<String, AocError> {
+        let centers = &self.layout()?.centers;
 
         let ans = centers
             .iter()
@@ -149,24 +170,32 @@ impl Day for Day19 {
             .max()
             .unwrap();
 
-        ans.to_string()
+        Ok(ans.to_string())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     // TODO: This is too slow to run with other tests
     #[test]
     #[ignore]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day19.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day19>(19);
+    }
 
-        let day = Day19::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "449");
-        assert_eq!(day.part2(), "13128");
+    #[test]
+    fn test_lazy_layout() {
+        // Placing the scanners waits for a part, which reports the failure instead of panicking
+        let input = "--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6\n";
+        let day = Day19::new(&mut input.as_bytes()).unwrap();
+        assert!(day.layout.get().is_none());
+
+        let err = day.try_part2().unwrap_err();
+        assert_eq!(err.to_string(), "scanner 1 doesn't overlap with the others");
+        assert!(day.try_part1().is_err());
     }
 }
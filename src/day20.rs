@@ -1,9 +1,9 @@
 use crate::day::Day;
-use crate::util::Point;
+use crate::error::AocError;
+use crate::util::{self, Point};
 
 use std::collections::HashSet;
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 
 pub struct Day20 {
     rules: Vec<bool>,
@@ -32,7 +32,7 @@ impl Day20 {
                     self.rules[0]
                 };
 
-                let cands: HashSet<Point> = points.iter().flat_map(Point::get_adj9).collect();
+                let cands: HashSet<Point> = points.iter().flat_map(|&p| p.get_adj9()).collect();
 
                 let next_points = cands
                     .into_iter()
@@ -55,20 +55,43 @@ impl Day20 {
 }
 
 impl Day for Day20 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut rules = String::new();
-        reader.read_line(&mut rules)?;
-        let rules: Vec<bool> = rules.trim().bytes().map(|b| b == b'#').collect();
-
-        assert_eq!(rules.len(), 512);
-
-        let grid: Vec<Vec<bool>> = reader
-            .lines()
-            .skip(1) // Skip the empty line between rules and grid
-            .map(|line_res| {
-                line_res.map(|line| line.bytes().map(|c| c == b'#').collect::<Vec<bool>>())
-            })
-            .collect::<io::Result<_>>()?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        fn parse_pixels(row: &str, line: usize) -> Result<Vec<bool>, AocError> {
+            row.bytes()
+                .enumerate()
+                .map(|(col_idx, c)| match c {
+                    b'#' => Ok(true),
+                    b'.' => Ok(false),
+                    _ => Err(AocError::at(line, col_idx + 1, "expected '#' or '.'")),
+                })
+                .collect()
+        }
+
+        let blocks = util::read_blocks(reader)?;
+        let [rules, image] = blocks.as_slice() else {
+            return Err(AocError::parse(format!(
+                "expected the rules and the image separated by a blank line, found {} blocks",
+                blocks.len()
+            )));
+        };
+
+        let rules: Vec<bool> = match rules.lines.as_slice() {
+            [line] => parse_pixels(line, rules.first_line)?,
+            _ => return Err(AocError::parse("expected the rules on one line").at_line(1)),
+        };
+
+        if rules.len() != 512 {
+            return Err(AocError::parse(format!(
+                "expected 512 enhancement rules, found {}",
+                rules.len()
+            ))
+            .at_line(1));
+        }
+
+        let grid: Vec<Vec<bool>> = image
+            .numbered()
+            .map(|(line, row)| parse_pixels(row, line))
+            .collect::<Result<_, _>>()?;
 
         let start: Vec<Point> = grid
             .into_iter()
@@ -101,13 +124,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day20.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day20::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "5479");
-        assert_eq!(day.part2(), "19012");
+        answers::test_day::<Day20>(20);
     }
 }
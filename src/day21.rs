@@ -1,9 +1,8 @@
 use crate::day::Day;
-use crate::util::ParseInputError;
+use crate::error::{at_line, AocError};
 
 use std::cmp;
 use std::collections::HashMap;
-use std::error::Error;
 use std::io::BufRead;
 
 // (number of turns, final scores)
@@ -46,7 +45,6 @@ fn play_quantum(start: [u32; 2], sides: u32, max_score: u32) -> [u64; 2] {
     fn solve(
         memo: &mut HashMap<([u32; 2], [u32; 2]), [u64; 2]>,
         opts: &[u64],
-        sides: u32,
         max_score: u32,
         pos: [u32; 2],
         score: [u32; 2],
@@ -74,7 +72,6 @@ fn play_quantum(start: [u32; 2], sides: u32, max_score: u32) -> [u64; 2] {
                 let ways = solve(
                     memo,
                     opts,
-                    sides,
                     max_score,
                     [pos[1], new_pos],
                     [score[1], new_score],
@@ -101,7 +98,7 @@ fn play_quantum(start: [u32; 2], sides: u32, max_score: u32) -> [u64; 2] {
         }
     }
 
-    solve(&mut memo, &opts, sides, max_score, start, [0, 0])
+    solve(&mut memo, &opts, max_score, start, [0, 0])
 }
 
 pub struct Day21 {
@@ -109,18 +106,27 @@ pub struct Day21 {
 }
 
 impl Day for Day21 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
         let mut start: [u32; 2] = Default::default();
 
-        for pos in &mut start {
+        for (line_idx, pos) in start.iter_mut().enumerate() {
             let mut line = String::new();
             reader.read_line(&mut line)?;
 
             let x: u32 = line
                 .split_whitespace()
-                .last()
-                .ok_or_else(|| ParseInputError(line.clone()))?
-                .parse()?;
+                .next_back()
+                .ok_or_else(|| AocError::parse("expected a starting position"))
+                .and_then(|x| Ok(x.parse()?))
+                .map_err(at_line(line_idx + 1))?;
+
+            if !(1..=10).contains(&x) {
+                return Err(AocError::parse(format!(
+                    "starting position {} is not between 1 and 10",
+                    x
+                ))
+                .at_line(line_idx + 1));
+            }
 
             *pos = x - 1;
         }
@@ -146,13 +152,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day21.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day21::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "1002474");
-        assert_eq!(day.part2(), "919758187195363");
+        answers::test_day::<Day21>(21);
     }
 }
@@ -1,8 +1,8 @@
 use crate::day::Day;
-use crate::util::ParseInputError;
+use crate::error::AocError;
+use crate::util;
 
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -27,38 +27,61 @@ struct Command {
 }
 
 impl FromStr for Command {
-    type Err = ParseInputError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split_whitespace().collect();
-        let command_type = match parts.get(0) {
-            Some(&"on") => Ok(CommandType::On),
-            Some(&"off") => Ok(CommandType::Off),
-            _ => Err(ParseInputError(s.to_string())),
-        }?;
-
-        let bounds: Vec<(i64, i64)> = parts[1]
-            .split(',')
-            .map(|block| {
-                // strip out var name and equals sign
-                let block = &block[2..];
-                let bound: Result<Vec<_>, _> = block
-                    .split("..")
-                    .map(|bound| {
-                        bound
-                            .parse()
-                            .map_err(|_| ParseInputError(block.to_string()))
-                    })
-                    .collect();
-
-                // Change from inclusive to exclusive upper bound
-                bound.map(|bound| (bound[0], bound[1] + 1))
-            })
-            .collect::<Result<_, _>>()?;
+        let (command_type, cuboid) = s
+            .split_once(' ')
+            .ok_or_else(|| AocError::parse("expected `on|off x=lo..hi,y=lo..hi,z=lo..hi`"))?;
+
+        let mut column = command_type.len() + 2;
+        let command_type = match command_type {
+            "on" => CommandType::On,
+            "off" => CommandType::Off,
+            _ => return Err(AocError::parse("expected on or off").at_column(1)),
+        };
+
+        let mut bounds = [(0, 0); 3];
+        let mut blocks = cuboid.split(',');
+        for (bound, axis) in bounds.iter_mut().zip(['x', 'y', 'z']) {
+            let block = blocks.next().ok_or_else(|| {
+                AocError::parse(format!("missing {} range", axis)).at_column(column)
+            })?;
+
+            // strip out var name and equals sign
+            let (lo, hi) = block
+                .strip_prefix(axis)
+                .and_then(|block| block.strip_prefix('='))
+                .and_then(|block| block.split_once(".."))
+                .ok_or_else(|| {
+                    AocError::parse(format!("expected `{}=lo..hi`", axis)).at_column(column)
+                })?;
+
+            let lo_column = column + 2;
+            let hi_column = lo_column + lo.len() + 2;
+            let lo: i64 = lo
+                .parse()
+                .map_err(|e| AocError::from(e).at_column(lo_column))?;
+            let hi: i64 = hi
+                .parse()
+                .map_err(|e| AocError::from(e).at_column(hi_column))?;
+
+            if lo > hi {
+                return Err(AocError::parse(format!("empty {} range", axis)).at_column(column));
+            }
+
+            // Change from inclusive to exclusive upper bound
+            *bound = (lo, hi + 1);
+            column += block.len() + 1;
+        }
+
+        if blocks.next().is_some() {
+            return Err(AocError::parse("expected exactly three ranges").at_column(column - 1));
+        }
 
         Ok(Self {
             command_type,
-            bounds: [bounds[0], bounds[1], bounds[2]],
+            bounds,
         })
     }
 }
@@ -147,11 +170,8 @@ pub struct Day22 {
 }
 
 impl Day for Day22 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let commands: Vec<Command> = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.parse()))
-            .collect::<io::Result<Result<Vec<Command>, _>>>()??;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let commands: Vec<Command> = util::parse_lines(reader)?;
 
         Ok(Self { commands })
     }
@@ -160,13 +180,13 @@ impl Day for Day22 {
         let small_commands: Vec<Command> = self
             .commands
             .iter()
-            .cloned()
             .filter(|command| {
                 command
                     .bounds
                     .iter()
                     .all(|bound| -50 <= bound.0 && bound.1 <= 51)
             })
+            .cloned()
             .collect();
 
         solve(&small_commands).to_string()
@@ -179,17 +199,18 @@ impl Day for Day22 {
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn test_example() {
+        use crate::*;
+
+        answers::test_input::<Day22>(22, "example");
+    }
+
     #[test]
     #[ignore]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day22.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day22::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "556501");
-        assert_eq!(day.part2(), "1217140271559773");
+        answers::test_day::<Day22>(22);
     }
 }
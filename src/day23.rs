@@ -1,8 +1,8 @@
 use crate::day::Day;
+use crate::error::AocError;
+use crate::util::search;
 
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap};
-use std::error::Error;
+use std::collections::HashMap;
 use std::io::BufRead;
 use std::iter;
 use std::str;
@@ -27,14 +27,6 @@ struct StateInfo {
     filled: Vec<bool>,
 }
 
-fn absdiff(a: usize, b: usize) -> usize {
-    if a > b {
-        a - b
-    } else {
-        b - a
-    }
-}
-
 impl StateInfo {
     fn new(state: &[u8]) -> Self {
         let slot_data = &state[HALL_LEN..];
@@ -138,8 +130,7 @@ impl StateInfo {
         let coeff = POW10[usize::from(amphipod)];
         let (slot, depth) = self.get_slot(dest);
 
-        // TODO: abs_diff
-        let dist = absdiff(src, SLOT2HALL[slot]) + 1 + depth;
+        let dist = src.abs_diff(SLOT2HALL[slot]) + 1 + depth;
 
         coeff * (dist as u64)
     }
@@ -180,8 +171,7 @@ impl StateInfo {
 }
 
 fn build_state(slots: &[Vec<u8>; NUM_SLOTS]) -> Vec<u8> {
-    iter::repeat(EMPTY)
-        .take(HALL_LEN)
+    iter::repeat_n(EMPTY, HALL_LEN)
         .chain(slots.iter().flat_map(|slot| slot.iter().copied()))
         .collect()
 }
@@ -205,81 +195,61 @@ fn print_state(state: &[u8]) -> String {
     str::from_utf8(&translated).expect("valid utf8").to_string()
 }
 
+/// Every state one move away from `state`, along with the energy that move takes
+fn successors(state: &[u8]) -> Vec<(Vec<u8>, u64)> {
+    let state_info = &StateInfo::new(state);
+
+    let moved = |src: usize, dest: usize, weight: u64| {
+        let mut next_state = state.to_vec();
+        next_state[dest] = state[src];
+        next_state[src] = EMPTY;
+
+        (next_state, weight)
+    };
+
+    // for each hallway spot, see if we can move the amphipod to the correct slot
+    let to_slot = (0..HALL_LEN)
+        .filter(|&src| state[src] != EMPTY && state_info.can_move_to_slot(src, state[src]))
+        .map(|src| {
+            let dest = state_info.get_dest(state[src]);
+            moved(src, dest, state_info.get_dist(state[src], src, dest))
+        });
+
+    // for each slot, try and move the top amphipod in the slot to the hallway.
+    let to_hall = (0..NUM_SLOTS)
+        .filter(|slot| !state_info.can_place[*slot])
+        .flat_map(|slot| {
+            let src = state_info.top_amphipod(slot);
+
+            (0..HALL_LEN)
+                .filter(move |&dest| state_info.can_move_to_hall(slot, dest))
+                .map(move |dest| (src, dest))
+        })
+        .map(|(src, dest)| moved(src, dest, state_info.get_dist(state[src], dest, src)));
+
+    to_slot.chain(to_hall).collect()
+}
+
 fn solve(slots: &[Vec<u8>; NUM_SLOTS]) -> u64 {
     let depth = slots[0].len();
     assert!(slots.iter().all(|slot| slot.len() == depth));
 
     let seed = build_state(slots);
+    // Only the depth matters for the heuristic and the goal check, which is the same for all states
     let seed_info = StateInfo::new(&seed);
 
-    let mut dists: HashMap<Vec<u8>, u64> = HashMap::new();
-    dists.insert(seed.clone(), 0);
-
-    let mut heap: BinaryHeap<Reverse<(u64, u64, Vec<u8>)>> = BinaryHeap::new();
-    let seed_heuristic = seed_info.heuristic(&seed);
-    heap.push(Reverse((seed_heuristic, 0, seed)));
-
-    while let Some(Reverse((_, dist, state))) = heap.pop() {
-        let state_info = StateInfo::new(&state);
-        if state_info.is_done(&state) {
-            return dist;
-        }
-
-        if dists.get(&state).unwrap_or(&u64::MAX) != &dist {
-            continue;
-        }
-
-        // for each hallway spot, see if we can move the amphipod to the correct slot
-        let to_slot = (0..HALL_LEN)
-            .filter(|&src| state[src] != EMPTY && state_info.can_move_to_slot(src, state[src]))
-            .map(|src| {
-                let mut next_state: Vec<u8> = state.clone();
-                let dest = state_info.get_dest(state[src]);
-                next_state[dest] = state[src];
-                next_state[src] = EMPTY;
-
-                let weight = state_info.get_dist(state[src], src, dest);
-
-                (dist + weight, next_state)
-            });
-
-        // for each slot, try and move the top amphipod in the slot to the hallway.
-        let to_hall = (0..NUM_SLOTS)
-            .filter(|slot| !state_info.can_place[*slot])
-            .flat_map(|slot| {
-                let src = state_info.top_amphipod(slot);
-
-                // TODO: remove intermediate allocation
-                (0..HALL_LEN)
-                    .filter(|&dest| state_info.can_move_to_hall(slot, dest))
-                    .map(|dest| {
-                        let mut next_state: Vec<u8> = state.clone();
-                        next_state[dest] = state[src];
-                        next_state[src] = EMPTY;
-
-                        let weight = state_info.get_dist(state[src], dest, src);
-
-                        (dist + weight, next_state)
-                    })
-                    .collect::<Vec<_>>()
-                    .into_iter()
-            });
-
-        for (cost, next_state) in to_slot.chain(to_hall) {
-            let cur_cost = dists.entry(next_state.clone()).or_insert(u64::MAX);
-
-            if cost < *cur_cost {
-                *cur_cost = cost;
-                heap.push(Reverse((
-                    cost + state_info.heuristic(&next_state),
-                    cost,
-                    next_state,
-                )));
-            }
-        }
-    }
-
-    unreachable!()
+    // Only the energy is needed, so skip remembering how each state was reached
+    let search = search::astar_with(
+        HashMap::new(),
+        seed,
+        |state| successors(state),
+        |state| seed_info.heuristic(state),
+        |state| seed_info.is_done(state),
+    );
+
+    search
+        .goal_dist()
+        .expect("the amphipods can always be organized")
 }
 
 pub struct Day23 {
@@ -287,16 +257,57 @@ pub struct Day23 {
 }
 
 impl Day for Day23 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let mut buf = vec![];
-        reader.read_to_end(&mut buf)?;
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        // The burrow rows are the only lines with amphipods on them; everything else is walls
+        let mut rows = vec![];
+        for (line_idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            let mut row = vec![];
+            for (col_idx, c) in line.bytes().enumerate() {
+                match c {
+                    b'A'..=b'D' => row.push(c - b'A'),
+                    b'#' | b'.' | b' ' => {}
+                    _ => {
+                        return Err(AocError::at(
+                            line_idx + 1,
+                            col_idx + 1,
+                            format!("unexpected {:?}", c as char),
+                        ))
+                    }
+                }
+            }
 
-        let slots = [
-            vec![buf[31] - b'A', buf[45] - b'A'],
-            vec![buf[33] - b'A', buf[47] - b'A'],
-            vec![buf[35] - b'A', buf[49] - b'A'],
-            vec![buf[37] - b'A', buf[51] - b'A'],
-        ];
+            if !row.is_empty() {
+                if row.len() != 4 {
+                    return Err(
+                        AocError::parse("expected 4 amphipods per row").at_line(line_idx + 1)
+                    );
+                }
+                rows.push(row);
+            }
+        }
+
+        if rows.len() != 2 {
+            return Err(AocError::parse(format!(
+                "expected 2 rows of amphipods, found {}",
+                rows.len()
+            )));
+        }
+
+        // Anything but two of each kind can't be organized
+        let mut counts = [0; NUM_SLOTS];
+        for &amphipod in rows.iter().flatten() {
+            counts[amphipod as usize] += 1;
+        }
+        if let Some(kind) = counts.iter().position(|&count| count != 2) {
+            return Err(AocError::parse(format!(
+                "expected 2 amphipods of type {}, found {}",
+                (b'A' + kind as u8) as char,
+                counts[kind]
+            )));
+        }
+
+        let slots = [0, 1, 2, 3].map(|idx| vec![rows[0][idx], rows[1][idx]]);
 
         Ok(Self { slots })
     }
@@ -318,16 +329,22 @@ impl Day for Day23 {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
 
-        let data = include_bytes!("../data_files/day23.txt");
-        let mut reader = BufReader::new(&data[..]);
+        answers::test_day::<Day23>(23);
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = "#############\n#...........#\n###B#C#B#D###\n  #A#D#C#A#\n  #########\n";
+        assert!(Day23::new(&mut input.as_bytes()).is_ok());
 
-        let day = Day23::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "12530");
-        assert_eq!(day.part2(), "50492");
+        let input = input.replace("#A#D", "#B#D");
+        let err = Day23::new(&mut input.as_bytes()).err().unwrap();
+        assert_eq!(err.to_string(), "expected 2 amphipods of type A, found 1");
     }
 }
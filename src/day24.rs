@@ -1,7 +1,7 @@
 use crate::day::Day;
+use crate::error::{at_line, AocError};
 
 use std::collections::HashMap;
-use std::error::Error;
 use std::io::{self, BufRead};
 use std::iter;
 
@@ -58,20 +58,33 @@ fn solve(data: &[(i64, i64)], is_maximum: bool) -> i64 {
 }
 
 impl Day for Day24 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let commands: Vec<String> = reader.lines().collect::<io::Result<_>>()?;
-
-        let data: Vec<(i64, i64)> = commands
-            .chunks(18)
-            .map(|chunk| {
-                let c1 = chunk[5].split_whitespace().last().unwrap_or("");
-                let c2 = chunk[15].split_whitespace().last().unwrap_or("");
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        const BLOCK_LEN: usize = 18;
 
-                c1.parse().and_then(|c1| c2.parse().map(|c2| (c1, c2)))
-            })
-            .collect::<Result<_, _>>()?;
+        let commands: Vec<String> = reader.lines().collect::<io::Result<_>>()?;
+        if commands.is_empty() || !commands.len().is_multiple_of(BLOCK_LEN) {
+            return Err(AocError::parse(format!(
+                "expected blocks of {} instructions, found {} lines",
+                BLOCK_LEN,
+                commands.len()
+            )));
+        }
+
+        // The constants sit at the end of the 6th and 16th instruction of each block
+        let constant = |line_idx: usize| {
+            commands[line_idx]
+                .split_whitespace()
+                .last()
+                .unwrap_or("")
+                .parse::<i64>()
+                .map_err(at_line(line_idx + 1))
+        };
+
+        let data: Vec<(i64, i64)> = (0..commands.len())
+            .step_by(BLOCK_LEN)
+            .map(|start| Ok((constant(start + 5)?, constant(start + 15)?)))
+            .collect::<Result<_, AocError>>()?;
 
-        dbg!(&data);
         Ok(Self { data })
     }
 
@@ -89,13 +102,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day24.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day24::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "91897399498995");
-        assert_eq!(day.part2(), "51121176121391");
+        answers::test_day::<Day24>(24);
     }
 }
@@ -1,9 +1,9 @@
 use crate::day::Day;
+use crate::error::AocError;
 use crate::util::Grid;
 
 use std::collections::HashSet;
-use std::error::Error;
-use std::io::{self, BufRead};
+use std::io::BufRead;
 use std::iter;
 
 const fn inc(x: usize, m: usize) -> usize {
@@ -95,42 +95,26 @@ impl Day25 {
 }
 
 impl Day for Day25 {
-    fn new<R: BufRead>(reader: &mut R) -> Result<Self, Box<dyn Error>> {
-        let grid: Grid = reader
-            .lines()
-            .map(|line_res| line_res.map(|line| line.bytes().collect()))
-            .collect::<io::Result<_>>()?;
-
-        let n = grid.len();
-        let m = grid[0].len();
-
-        assert!(grid.iter().all(|row| row.len() == m));
-
-        let easts: Vec<(usize, usize)> = grid
-            .iter()
-            .enumerate()
-            .flat_map(|(r, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter_map(move |(c, cell)| match cell {
-                        b'>' => Some((r, c)),
-                        _ => None,
-                    })
-            })
-            .collect();
-
-        let souths: Vec<(usize, usize)> = grid
-            .iter()
-            .enumerate()
-            .flat_map(|(r, row)| {
-                row.iter()
-                    .enumerate()
-                    .filter_map(move |(c, cell)| match cell {
-                        b'v' => Some((r, c)),
-                        _ => None,
-                    })
-            })
-            .collect();
+    fn new<R: BufRead + ?Sized>(reader: &mut R) -> Result<Self, AocError> {
+        let mut input = String::new();
+        reader.read_to_string(&mut input)?;
+
+        let grid = Grid::parse_with(&input, |c| match c {
+            '>' | 'v' | '.' => Some(c),
+            _ => None,
+        })?;
+
+        let n = grid.height();
+        let m = grid.width();
+
+        let cucumbers = |kind: char| -> Vec<(usize, usize)> {
+            grid.iter()
+                .filter(|&(_, &cell)| cell == kind)
+                .map(|(pos, _)| pos)
+                .collect()
+        };
+        let easts = cucumbers('>');
+        let souths = cucumbers('v');
 
         Ok(Self {
             n,
@@ -154,12 +138,7 @@ mod tests {
     #[test]
     fn test_data() {
         use crate::*;
-        use std::io::BufReader;
-
-        let data = include_bytes!("../data_files/day25.txt");
-        let mut reader = BufReader::new(&data[..]);
 
-        let day = Day25::new(&mut reader).unwrap();
-        assert_eq!(day.part1(), "351");
+        answers::test_day::<Day25>(25);
     }
 }
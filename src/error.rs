@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+use std::time::Duration;
+
+/// Everything that can go wrong while reading a puzzle input or solving a day.
+///
+/// Line and column numbers are 1-based and optional, since most parsers only see part of the
+/// input. Whoever knows the position fills it in on the way out, see `at_line`.
+#[derive(Debug)]
+pub enum AocError {
+    /// Reading the input failed
+    Io(io::Error),
+    /// A number in the input didn't parse
+    ParseInt {
+        source: ParseIntError,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    /// The input doesn't have the structure the puzzle promises
+    Parse {
+        msg: String,
+        line: Option<usize>,
+        column: Option<usize>,
+    },
+    /// There is no solution for the requested day
+    UnsupportedDay(u8),
+    /// The solver panicked, with the panic message if there was one
+    Panic(String),
+    /// The solver didn't finish within the time budget
+    Timeout(Duration),
+}
+
+impl AocError {
+    pub fn parse<S: Into<String>>(msg: S) -> Self {
+        AocError::Parse {
+            msg: msg.into(),
+            line: None,
+            column: None,
+        }
+    }
+
+    pub fn at<S: Into<String>>(line: usize, column: usize, msg: S) -> Self {
+        AocError::Parse {
+            msg: msg.into(),
+            line: Some(line),
+            column: Some(column),
+        }
+    }
+
+    fn location_mut(&mut self) -> Option<(&mut Option<usize>, &mut Option<usize>)> {
+        match self {
+            AocError::ParseInt { line, column, .. } | AocError::Parse { line, column, .. } => {
+                Some((line, column))
+            }
+            _ => None,
+        }
+    }
+
+    /// Set the line number if it isn't known yet
+    pub fn at_line(mut self, line: usize) -> Self {
+        if let Some((cur, _)) = self.location_mut() {
+            cur.get_or_insert(line);
+        }
+
+        self
+    }
+
+    /// Set the column number if it isn't known yet
+    pub fn at_column(mut self, column: usize) -> Self {
+        if let Some((_, cur)) = self.location_mut() {
+            cur.get_or_insert(column);
+        }
+
+        self
+    }
+
+    /// Shift a known column by `offset`, for errors from parsing a substring of a line
+    pub fn offset_column(mut self, offset: usize) -> Self {
+        if let Some((_, Some(cur))) = self.location_mut() {
+            *cur += offset;
+        }
+
+        self
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            AocError::ParseInt { line, .. } | AocError::Parse { line, .. } => *line,
+            _ => None,
+        }
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        match self {
+            AocError::ParseInt { column, .. } | AocError::Parse { column, .. } => *column,
+            _ => None,
+        }
+    }
+}
+
+/// Attach a 1-based line number to anything that converts into an `AocError`.
+/// Meant for `map_err` when iterating over the lines of an input.
+pub fn at_line<E: Into<AocError>>(line: usize) -> impl FnOnce(E) -> AocError {
+    move |e| e.into().at_line(line)
+}
+
+impl fmt::Display for AocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line(), self.column()) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            (None, Some(column)) => write!(f, "column {}: ", column)?,
+            (None, None) => {}
+        }
+
+        match self {
+            AocError::Io(e) => e.fmt(f),
+            AocError::ParseInt { source, .. } => write!(f, "bad number: {}", source),
+            AocError::Parse { msg, .. } => msg.fmt(f),
+            AocError::UnsupportedDay(day) => write!(f, "no solution for day {}", day),
+            AocError::Panic(msg) => write!(f, "panicked: {}", msg),
+            AocError::Timeout(budget) => write!(f, "timed out after {:?}", budget),
+        }
+    }
+}
+
+impl Error for AocError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            AocError::Io(e) => Some(e),
+            AocError::ParseInt { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for AocError {
+    fn from(e: io::Error) -> Self {
+        AocError::Io(e)
+    }
+}
+
+impl From<ParseIntError> for AocError {
+    fn from(source: ParseIntError) -> Self {
+        AocError::ParseInt {
+            source,
+            line: None,
+            column: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let err = AocError::parse("expected a number");
+        assert_eq!(err.to_string(), "expected a number");
+
+        let err = err.at_column(3).at_line(7).at_line(9);
+        assert_eq!(err.to_string(), "line 7, column 3: expected a number");
+
+        let err = AocError::from("x".parse::<i64>().unwrap_err()).at_line(2);
+        assert_eq!(
+            err.to_string(),
+            "line 2: bad number: invalid digit found in string"
+        );
+
+        let err = AocError::at(1, 2, "oops").offset_column(10);
+        assert_eq!(err.to_string(), "line 1, column 12: oops");
+    }
+}
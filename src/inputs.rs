@@ -0,0 +1,70 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A named puzzle input for a day, stored at `<data dir>/dayNN/<name>.txt`.
+///
+/// `real` is the personal puzzle input, `example` the one from the puzzle text. Anything else
+/// (edge cases, other people's inputs, ...) can sit next to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Input {
+    pub day: u8,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+pub fn day_dir(data_dir: &Path, day: u8) -> PathBuf {
+    data_dir.join(format!("day{:02}", day))
+}
+
+/// Find every input for a day, sorted by name. A day without a directory has no inputs.
+pub fn discover(data_dir: &Path, day: u8) -> io::Result<Vec<Input>> {
+    let dir = day_dir(data_dir, day);
+    if !dir.is_dir() {
+        return Ok(vec![]);
+    }
+
+    in_dir(&dir, day)
+}
+
+/// Treat every `.txt` file in `dir` as an input for `day`, sorted by name
+pub fn in_dir(dir: &Path, day: u8) -> io::Result<Vec<Input>> {
+    let mut inputs = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "txt") {
+            continue;
+        }
+
+        if let Some(name) = path.file_stem().and_then(|name| name.to_str()) {
+            inputs.push(Input {
+                day,
+                name: name.to_string(),
+                path: path.clone(),
+            });
+        }
+    }
+
+    inputs.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover() {
+        let data_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("data_files");
+
+        let names: Vec<String> = discover(&data_dir, 1)
+            .unwrap()
+            .into_iter()
+            .map(|input| input.name)
+            .collect();
+        assert_eq!(names, ["example", "real"]);
+
+        assert!(discover(&data_dir, 26).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+//! Solutions to Advent of Code 2021.
+//!
+//! Each day is a type implementing [`Day`]. The [`registry`] lists every solved day, and
+//! [`run_day`] uses it to pick the solver for a day number at runtime. Reusable pieces
+//! (points, grids, snailfish numbers, ...) live in [`util`] and the day modules.
+
+pub mod answers;
+pub mod bench;
+pub mod day;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod day17;
+pub mod day18;
+pub mod day19;
+pub mod day20;
+pub mod day21;
+pub mod day22;
+pub mod day23;
+pub mod day24;
+pub mod day25;
+pub mod error;
+pub mod inputs;
+pub mod pool;
+pub mod registry;
+pub mod report;
+pub mod util;
+
+pub use crate::day::{Day, DynDay};
+pub use crate::day01::Day01;
+pub use crate::day02::Day02;
+pub use crate::day03::Day03;
+pub use crate::day04::Day04;
+pub use crate::day05::Day05;
+pub use crate::day06::Day06;
+pub use crate::day07::Day07;
+pub use crate::day08::Day08;
+pub use crate::day09::Day09;
+pub use crate::day10::Day10;
+pub use crate::day11::Day11;
+pub use crate::day12::Day12;
+pub use crate::day13::Day13;
+pub use crate::day14::Day14;
+pub use crate::day15::Day15;
+pub use crate::day16::Day16;
+pub use crate::day17::Day17;
+pub use crate::day18::Day18;
+pub use crate::day19::Day19;
+pub use crate::day20::Day20;
+pub use crate::day21::Day21;
+pub use crate::day22::Day22;
+pub use crate::day23::Day23;
+pub use crate::day24::Day24;
+pub use crate::day25::Day25;
+pub use crate::error::AocError;
+pub use crate::registry::{registry, DayEntry};
+
+use std::io::BufRead;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Which parts of a day to solve
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Parts {
+    #[default]
+    Both,
+    Part1,
+    Part2,
+}
+
+impl Parts {
+    pub fn part1(self) -> bool {
+        self != Parts::Part2
+    }
+
+    pub fn part2(self) -> bool {
+        self != Parts::Part1
+    }
+}
+
+impl FromStr for Parts {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "1" => Ok(Parts::Part1),
+            "2" => Ok(Parts::Part2),
+            _ => Err(AocError::parse(format!(
+                "expected part 1 or 2, found {:?}",
+                s
+            ))),
+        }
+    }
+}
+
+/// The answer to one part and how long it took
+pub struct PartResult {
+    pub answer: String,
+    pub time: Duration,
+}
+
+/// Answers for the parts of a day that were run, along with how long each phase took.
+pub struct DayResult {
+    pub parse_time: Duration,
+    pub part1: Option<PartResult>,
+    pub part2: Option<PartResult>,
+}
+
+impl DayResult {
+    /// Time spent parsing and solving the parts that were run
+    pub fn total_time(&self) -> Duration {
+        self.parse_time
+            + [&self.part1, &self.part2]
+                .into_iter()
+                .flatten()
+                .map(|part| part.time)
+                .sum::<Duration>()
+    }
+}
+
+/// Solve both parts of a day on the given input, timing each phase.
+pub fn run_day(day: u8, reader: &mut dyn BufRead) -> Result<DayResult, AocError> {
+    registry::lookup(day)?.run(reader, Parts::Both)
+}
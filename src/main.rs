@@ -1,113 +1,580 @@
+use std::cmp;
 use std::env;
 use std::error::Error;
-use std::io::{self, BufRead, BufReader};
-
-mod day;
-mod day01;
-mod day02;
-mod day03;
-mod day04;
-mod day05;
-mod day06;
-mod day07;
-mod day08;
-mod day09;
-mod day10;
-mod day11;
-mod day12;
-mod day13;
-mod day14;
-mod day15;
-mod day16;
-mod day17;
-mod day18;
-mod day19;
-mod day20;
-mod day21;
-mod day22;
-mod day23;
-mod day24;
-mod day25;
-mod util;
-
-use crate::day::*;
-use crate::day01::*;
-use crate::day02::*;
-use crate::day03::*;
-use crate::day04::*;
-use crate::day05::*;
-use crate::day06::*;
-use crate::day07::*;
-use crate::day08::*;
-use crate::day09::*;
-use crate::day10::*;
-use crate::day11::*;
-use crate::day12::*;
-use crate::day13::*;
-use crate::day14::*;
-use crate::day15::*;
-use crate::day16::*;
-use crate::day17::*;
-use crate::day18::*;
-use crate::day19::*;
-use crate::day20::*;
-use crate::day21::*;
-use crate::day22::*;
-use crate::day23::*;
-use crate::day24::*;
-use crate::day25::*;
-
-fn run<D: Day>(day: &D) -> (String, String) {
-    (day.part1(), day.part2())
-}
-
-fn run_day<R: BufRead>(day: u8, reader: &mut R) -> Result<(String, String), Box<dyn Error>> {
-    Ok(match day {
-        1 => run(&Day01::new(reader)?),
-        2 => run(&Day02::new(reader)?),
-        3 => run(&Day03::new(reader)?),
-        4 => run(&Day04::new(reader)?),
-        5 => run(&Day05::new(reader)?),
-        6 => run(&Day06::new(reader)?),
-        7 => run(&Day07::new(reader)?),
-        8 => run(&Day08::new(reader)?),
-        9 => run(&Day09::new(reader)?),
-        10 => run(&Day10::new(reader)?),
-        11 => run(&Day11::new(reader)?),
-        12 => run(&Day12::new(reader)?),
-        13 => run(&Day13::new(reader)?),
-        14 => run(&Day14::new(reader)?),
-        15 => run(&Day15::new(reader)?),
-        16 => run(&Day16::new(reader)?),
-        17 => run(&Day17::new(reader)?),
-        18 => run(&Day18::new(reader)?),
-        19 => run(&Day19::new(reader)?),
-        20 => run(&Day20::new(reader)?),
-        21 => run(&Day21::new(reader)?),
-        22 => run(&Day22::new(reader)?),
-        23 => run(&Day23::new(reader)?),
-        24 => run(&Day24::new(reader)?),
-        25 => run(&Day25::new(reader)?),
-        _ => panic!("Unsupported day {}", day),
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use aoc21::answers::{Answers, Verdict};
+use aoc21::bench::{self, BenchResult};
+use aoc21::inputs::{self, Input};
+use aoc21::pool;
+use aoc21::report::{self, Record};
+use aoc21::{registry, AocError, DayResult, PartResult, Parts};
+
+const DATA_DIR: &str = "data_files";
+const DATA_DIR_VAR: &str = "AOC21_DATA_DIR";
+
+/// Parse a day selection such as `all`, `7` or `3-9,12` into a sorted list of unique days.
+/// Every selected day must be in the registry.
+fn parse_days(spec: &str) -> Result<Vec<u8>, AocError> {
+    if spec == "all" {
+        return Ok(registry().iter().map(|entry| entry.number).collect());
+    }
+
+    let parse_day = |s: &str| -> Result<u8, AocError> {
+        s.trim()
+            .parse()
+            .map_err(|_| AocError::parse(format!("Invalid day {:?}", s)))
+    };
+
+    let mut days = vec![];
+    for part in spec.split(',') {
+        match part.split_once('-') {
+            Some((lo, hi)) => {
+                let lo = parse_day(lo)?;
+                let hi = parse_day(hi)?;
+                if lo > hi {
+                    return Err(AocError::parse(format!("Empty day range {:?}", part)));
+                }
+
+                days.extend(lo..=hi);
+            }
+            None => days.push(parse_day(part)?),
+        }
+    }
+
+    days.sort_unstable();
+    days.dedup();
+
+    for &day in &days {
+        registry::lookup(day)?;
+    }
+
+    Ok(days)
+}
+
+/// Where the data files live: `--data-dir`, then `$AOC21_DATA_DIR`, then `data_files`
+fn data_dir(flag: Option<PathBuf>) -> PathBuf {
+    flag.or_else(|| env::var_os(DATA_DIR_VAR).map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from(DATA_DIR))
+}
+
+/// Where to read puzzle input from
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Source {
+    Stdin,
+    /// Every input of the day under the data directory
+    DataDir(PathBuf),
+    /// A single input file, or a directory of them
+    Path(PathBuf),
+}
+
+/// A loaded puzzle input, ready to run
+struct Job {
+    day: u8,
+    name: String,
+    input: io::Result<Vec<u8>>,
+}
+
+impl Job {
+    /// Solve the job on its own thread, so a panic or timeout only fails this job
+    fn run(&self, parts: Parts, timeout: Option<Duration>) -> Result<DayResult, AocError> {
+        match &self.input {
+            Ok(input) => registry::lookup(self.day)?.run_isolated(input.clone(), parts, timeout),
+            Err(e) => Err(io::Error::new(e.kind(), e.to_string()).into()),
+        }
+    }
+
+    fn error_row(&self, e: &AocError) -> [String; 6] {
+        [
+            self.day.to_string(),
+            self.name.clone(),
+            String::new(),
+            String::new(),
+            String::new(),
+            status(e),
+        ]
+    }
+}
+
+/// The status column for a job that failed
+fn status(e: &AocError) -> String {
+    match e {
+        AocError::Panic(msg) => format!("PANIC: {}", msg),
+        AocError::Timeout(_) => "TIMEOUT".to_string(),
+        e => format!("ERROR: {}", e),
+    }
+}
+
+/// A job for every input found in `dir`, or a single failing job if there are none
+fn dir_jobs(day: u8, dir: &Path, found: io::Result<Vec<Input>>) -> Vec<Job> {
+    match found {
+        Ok(found) if !found.is_empty() => found
+            .into_iter()
+            .map(|Input { day, name, path }| Job {
+                day,
+                name,
+                input: fs::read(path),
+            })
+            .collect(),
+        res => vec![Job {
+            day,
+            name: String::new(),
+            input: res.and_then(|_| {
+                Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("No inputs in {}", dir.display()),
+                ))
+            }),
+        }],
+    }
+}
+
+/// Load the input for each day. Reading from a directory gives a job for every input found
+/// in it.
+fn load_jobs(days: &[u8], source: &Source) -> Vec<Job> {
+    days.iter()
+        .flat_map(|&day| match source {
+            Source::Stdin => {
+                let mut buf = vec![];
+                let input = io::stdin().lock().read_to_end(&mut buf).map(|_| buf);
+                vec![Job {
+                    day,
+                    name: "stdin".to_string(),
+                    input,
+                }]
+            }
+            Source::DataDir(data_dir) => dir_jobs(
+                day,
+                &inputs::day_dir(data_dir, day),
+                inputs::discover(data_dir, day),
+            ),
+            Source::Path(path) if path.is_dir() => dir_jobs(day, path, inputs::in_dir(path, day)),
+            Source::Path(path) => vec![Job {
+                day,
+                name: path.file_stem().map_or_else(
+                    || path.display().to_string(),
+                    |name| name.to_string_lossy().into_owned(),
+                ),
+                input: fs::read(path),
+            }],
+        })
+        .collect()
+}
+
+/// Print rows as an aligned table. Multi-line cells (day 13) span several lines of their row.
+fn print_table<const N: usize>(header: [&str; N], rows: &[[String; N]]) {
+    let mut widths = header.map(str::len);
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = cmp::max(*width, cell.lines().map(str::len).max().unwrap_or(0));
+        }
+    }
+
+    let print_line = |line: [&str; N]| {
+        let padded: Vec<String> = line
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+
+        println!("{}", padded.join(" | ").trim_end());
+    };
+
+    print_line(header);
+    println!(
+        "{}",
+        widths
+            .iter()
+            .map(|&width| "-".repeat(width))
+            .collect::<Vec<_>>()
+            .join("-+-")
+    );
+
+    for row in rows {
+        let lines: Vec<Vec<&str>> = row.iter().map(|cell| cell.lines().collect()).collect();
+        let height = lines.iter().map(Vec::len).max().unwrap_or(0);
+
+        for idx in 0..cmp::max(height, 1) {
+            print_line(std::array::from_fn(|col| {
+                lines[col].get(idx).copied().unwrap_or("")
+            }));
+        }
+    }
+}
+
+const TABLE_HEADER: [&str; 6] = ["Day", "Input", "Part 1", "Part 2", "Time (ms)", "Status"];
+
+/// Run every job on up to `threads` threads, returning the results in job order. Each result
+/// is timed on the thread that ran it.
+fn run_jobs(
+    jobs: &[Job],
+    parts: Parts,
+    threads: usize,
+    timeout: Option<Duration>,
+) -> Vec<Result<DayResult, AocError>> {
+    pool::map(jobs, threads, |job| job.run(parts, timeout))
+}
+
+/// Table cell for a part that may not have been run
+fn answer_cell(part: &Option<PartResult>) -> String {
+    part.as_ref()
+        .map_or_else(|| "-".to_string(), |part| part.answer.clone())
+}
+
+/// A table row for each job with its answers and status.
+fn result_rows(jobs: &[Job], results: &[Result<DayResult, AocError>]) -> Vec<[String; 6]> {
+    jobs.iter()
+        .zip(results)
+        .map(|(job, res)| match res {
+            Ok(res) => [
+                job.day.to_string(),
+                job.name.clone(),
+                answer_cell(&res.part1),
+                answer_cell(&res.part2),
+                bench::millis(res.total_time()),
+                "OK".to_string(),
+            ],
+            Err(e) => job.error_row(e),
+        })
+        .collect()
+}
+
+/// Compare the result of every job against the expected answers, returning a table row for
+/// each along with whether everything known matched.
+fn check_all(
+    jobs: &[Job],
+    results: Vec<Result<DayResult, AocError>>,
+    answers: &Answers,
+) -> (Vec<[String; 6]>, bool) {
+    let mut ok = true;
+    let rows = jobs
+        .iter()
+        .zip(results)
+        .map(|(job, res)| {
+            let res = match res {
+                Ok(res) => res,
+                Err(e) => {
+                    ok = false;
+                    return job.error_row(&e);
+                }
+            };
+
+            let expected = answers.get(job.day, &job.name).cloned().unwrap_or_default();
+            let mut cell = |expected: Option<String>, actual: Option<PartResult>| {
+                let Some(PartResult { answer: actual, .. }) = actual else {
+                    return "-".to_string();
+                };
+
+                let verdict = Verdict::check(expected.as_deref(), &actual);
+                match (verdict, expected) {
+                    (Verdict::Fail, Some(expected)) => {
+                        ok = false;
+                        format!("{}\ngot:\n{}\nexpected:\n{}", verdict, actual, expected)
+                    }
+                    _ => verdict.to_string(),
+                }
+            };
+
+            let time = bench::millis(res.total_time());
+            [
+                job.day.to_string(),
+                job.name.clone(),
+                cell(expected.part1, res.part1),
+                cell(expected.part2, res.part2),
+                time,
+                "OK".to_string(),
+            ]
+        })
+        .collect();
+
+    (rows, ok)
+}
+
+/// Benchmark every job, skipping the ones that fail after reporting them on stderr. Returns the
+/// results along with how many jobs failed.
+fn bench_all(
+    jobs: &[Job],
+    parts: Parts,
+    reps: usize,
+    timeout: Option<Duration>,
+) -> (Vec<BenchResult>, usize) {
+    let mut results = vec![];
+    let mut failed = 0;
+    for job in jobs {
+        let status = match &job.input {
+            Ok(input) => match registry::lookup(job.day)
+                .and_then(|entry| bench::bench_day(entry, &job.name, input, parts, reps, timeout))
+            {
+                Ok(res) => {
+                    results.push(res);
+                    continue;
+                }
+                Err(e) => status(&e),
+            },
+            Err(e) => format!("ERROR: {}", e),
+        };
+
+        eprintln!("day {} {}: {}", job.day, job.name, status);
+        failed += 1;
+    }
+
+    (results, failed)
+}
+
+/// Print every solved day along with the inputs checked in for it
+fn list_days(data_dir: &Path) {
+    let rows: Vec<[String; 3]> = registry()
+        .iter()
+        .map(|entry| {
+            let names = inputs::discover(data_dir, entry.number)
+                .map(|found| {
+                    found
+                        .into_iter()
+                        .map(|input| input.name)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_else(|e| format!("ERROR: {}", e));
+
+            [entry.number.to_string(), entry.title.to_string(), names]
+        })
+        .collect();
+
+    print_table(["Day", "Title", "Inputs"], &rows);
+}
+
+/// How to print the answers of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Table,
+    Json,
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(AocError::parse(format!("Unknown format {:?}", s))),
+        }
+    }
+}
+
+struct Options {
+    selection: String,
+    run: bool,
+    input: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    parts: Parts,
+    format: Option<Format>,
+    jobs: usize,
+    timeout: Option<Duration>,
+    check: bool,
+    answers: Option<PathBuf>,
+    bench: bool,
+    reps: usize,
+    csv: Option<PathBuf>,
+}
+
+const USAGE: &str = concat!(
+    "usage: aoc21 [run] <day | all | 3-9,12> [--input PATH | -] [--data-dir DIR]\n",
+    "                                         [--part 1|2] [--format table|json|tsv]\n",
+    "                                         [--jobs N] [--timeout SECS]\n",
+    "                                         [--check] [--answers PATH]\n",
+    "                                         [--bench] [--reps N] [--csv PATH]\n",
+    "       aoc21 list [--data-dir DIR]\n",
+    "\n",
+    "A bare day number reads its input from stdin, `run` defaults to the data files.\n",
+    "The data directory defaults to $AOC21_DATA_DIR, then data_files.",
+);
+
+fn parse_args(args: &[String]) -> Result<Options, Box<dyn Error>> {
+    let mut selection = None;
+    let mut run = false;
+    let mut input = None;
+    let mut data_dir = None;
+    let mut parts = Parts::Both;
+    let mut format = None;
+    let mut jobs = 1;
+    let mut timeout = None;
+    let mut check = false;
+    let mut answers = None;
+    let mut bench = false;
+    let mut reps = 10;
+    let mut csv = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+
+        match arg.as_str() {
+            "--input" => input = Some(PathBuf::from(value()?)),
+            "--data-dir" => data_dir = Some(PathBuf::from(value()?)),
+            "--part" => parts = value()?.parse()?,
+            "--format" => format = Some(value()?.parse()?),
+            "--jobs" => jobs = value()?.parse()?,
+            "--timeout" => {
+                timeout = Some(
+                    Duration::try_from_secs_f64(value()?.parse()?)
+                        .map_err(|_| "--timeout must be a number of seconds")?,
+                )
+            }
+            "--check" => check = true,
+            "--answers" => answers = Some(PathBuf::from(value()?)),
+            "--bench" => bench = true,
+            "--reps" => reps = value()?.parse()?,
+            "--csv" => csv = Some(PathBuf::from(value()?)),
+            flag if flag.starts_with("--") => return Err(format!("Unknown flag {}", flag).into()),
+            "run" if selection.is_none() && !run => run = true,
+            _ if selection.is_none() => selection = Some(arg.clone()),
+            _ => return Err(format!("Unexpected argument {}", arg).into()),
+        }
+    }
+
+    if check && bench {
+        return Err("--check and --bench can't be combined".into());
+    }
+
+    if format.is_some() && (check || bench) {
+        return Err("--format can't be combined with --check or --bench".into());
+    }
+
+    if jobs == 0 {
+        return Err("--jobs must be positive".into());
+    }
+
+    if jobs > 1 && bench {
+        return Err("--bench runs one input at a time, so it can't be combined with --jobs".into());
+    }
+
+    if reps == 0 {
+        return Err("--reps must be positive".into());
+    }
+
+    let selection = selection.ok_or("Missing day selection")?;
+    Ok(Options {
+        selection,
+        run,
+        input,
+        data_dir,
+        parts,
+        format,
+        jobs,
+        timeout,
+        check,
+        answers,
+        bench,
+        reps,
+        csv,
     })
 }
 
 fn start() -> Result<(), Box<dyn Error>> {
     let args: Vec<String> = env::args().skip(1).collect();
 
-    if args.len() != 1 {
-        eprintln!("Invalid arguments. pass in a day");
-        return Err(Box::new(io::Error::from(io::ErrorKind::InvalidInput)));
+    let opts = match parse_args(&args) {
+        Ok(opts) => opts,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return Err(Box::new(io::Error::from(io::ErrorKind::InvalidInput)));
+        }
+    };
+
+    let data_dir = data_dir(opts.data_dir);
+
+    if opts.selection == "list" {
+        list_days(&data_dir);
+        return Ok(());
+    }
+
+    // An explicit input is only for a single day. Otherwise a bare day number reads its input
+    // from stdin, and anything else runs the data files.
+    let days = parse_days(&opts.selection)?;
+    let source = match opts.input {
+        Some(_) if days.len() != 1 => return Err("--input needs a single day".into()),
+        Some(path) if path == Path::new("-") => Source::Stdin,
+        Some(path) => Source::Path(path),
+        None if !opts.run && opts.selection.parse::<u8>().is_ok() => Source::Stdin,
+        None => Source::DataDir(data_dir.clone()),
+    };
+
+    let jobs = load_jobs(&days, &source);
+
+    if opts.bench {
+        let (results, failed) = bench_all(&jobs, opts.parts, opts.reps, opts.timeout);
+        print_table(bench::HEADER, &bench::table_rows(&results));
+
+        if let Some(path) = opts.csv {
+            bench::write_csv(File::create(path)?, &results, opts.reps)?;
+        }
+
+        if failed > 0 {
+            return Err(format!("{} input(s) failed to benchmark", failed).into());
+        }
+
+        return Ok(());
     }
 
-    let day: u8 = args[0].parse()?;
+    if opts.check {
+        let path = opts
+            .answers
+            .unwrap_or_else(|| data_dir.join("answers.toml"));
+        let answers = Answers::load(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let results = run_jobs(&jobs, opts.parts, opts.jobs, opts.timeout);
+        let (rows, ok) = check_all(&jobs, results, &answers);
+        print_table(TABLE_HEADER, &rows);
 
-    let stdin = std::io::stdin();
-    let stdin = stdin.lock();
-    let mut reader = BufReader::new(stdin);
+        if !ok {
+            return Err("Some answers did not match".into());
+        }
+
+        return Ok(());
+    }
+
+    if source == Source::Stdin && opts.format.is_none() {
+        let res = jobs[0].run(opts.parts, opts.timeout)?;
+        let answers: Vec<String> = [res.part1, res.part2]
+            .into_iter()
+            .flatten()
+            .map(|part| part.answer)
+            .collect();
+        println!("{}", answers.join(" "));
 
-    let (p1, p2) = run_day(day, &mut reader)?;
-    println!("{} {}", p1, p2);
+        return Ok(());
+    }
+
+    let results = run_jobs(&jobs, opts.parts, opts.jobs, opts.timeout);
+    let records: Vec<Record> = jobs
+        .iter()
+        .zip(&results)
+        .map(|(job, result)| Record {
+            day: job.day,
+            input: &job.name,
+            result,
+        })
+        .collect();
+
+    match opts.format.unwrap_or(Format::Table) {
+        Format::Table => print_table(TABLE_HEADER, &result_rows(&jobs, &results)),
+        Format::Json => report::write_json(io::stdout().lock(), &records)?,
+        Format::Tsv => report::write_tsv(io::stdout().lock(), &records)?,
+    }
+
+    let failed = results.iter().filter(|res| res.is_err()).count();
+    if failed > 0 {
+        return Err(format!("{} of {} inputs failed", failed, results.len()).into());
+    }
 
     Ok(())
 }
@@ -116,8 +583,27 @@ fn main() {
     std::process::exit(match start() {
         Ok(_) => 0,
         Err(e) => {
-            eprintln!("Error: {:?}", e);
+            eprintln!("Error: {}", e);
             1
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_days;
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(parse_days("all").unwrap(), (1..=25).collect::<Vec<u8>>());
+        assert_eq!(parse_days("7").unwrap(), vec![7]);
+        assert_eq!(parse_days("3-5,12").unwrap(), vec![3, 4, 5, 12]);
+        assert_eq!(parse_days("12,3-5,4").unwrap(), vec![3, 4, 5, 12]);
+
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("26").is_err());
+        assert!(parse_days("9-3").is_err());
+        assert!(parse_days("x").is_err());
+        assert!(parse_days("").is_err());
+    }
+}
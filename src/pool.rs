@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// Apply `f` to every item on up to `threads` worker threads, returning the results in the
+/// same order as `items`. Workers pull the next item as soon as they're free, so one slow item
+/// doesn't hold up the rest.
+pub fn map<T, R, F>(items: &[T], threads: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let threads = threads.clamp(1, items.len().max(1));
+    if threads == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let results: Vec<Mutex<Option<R>>> = items.iter().map(|_| Mutex::new(None)).collect();
+
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(idx) else {
+                    break;
+                };
+
+                let res = f(item);
+                *results[idx].lock().unwrap() = Some(res);
+            });
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|res| res.into_inner().unwrap().unwrap())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_map() {
+        let items: Vec<u64> = (0..20).collect();
+        let slow_square = |&x: &u64| {
+            thread::sleep(Duration::from_millis(20 - x));
+            x * x
+        };
+
+        let expected: Vec<u64> = items.iter().map(|x| x * x).collect();
+        assert_eq!(map(&items, 4, slow_square), expected);
+        assert_eq!(map(&items, 1, slow_square), expected);
+        assert_eq!(map(&[] as &[u64], 4, slow_square), vec![]);
+    }
+}
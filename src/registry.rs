@@ -0,0 +1,212 @@
+use crate::day::{Day, DynDay};
+use crate::error::AocError;
+use crate::*;
+
+use std::any::Any;
+use std::io::BufRead;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A solved day, constructed from its puzzle input
+pub type Constructor = fn(&mut dyn BufRead) -> Result<Box<dyn DynDay>, AocError>;
+
+/// Everything needed to find and run one day
+pub struct DayEntry {
+    pub number: u8,
+    pub title: &'static str,
+    pub new: Constructor,
+}
+
+impl DayEntry {
+    /// Solve the selected parts of the day on the given input, timing each phase.
+    pub fn run(&self, reader: &mut dyn BufRead, parts: Parts) -> Result<DayResult, AocError> {
+        let start = Instant::now();
+        let day = (self.new)(reader)?;
+        let parse_time = start.elapsed();
+
+        let time = |solve: &dyn Fn() -> Result<String, AocError>| {
+            let start = Instant::now();
+            let answer = solve()?;
+            Ok::<_, AocError>(PartResult {
+                answer,
+                time: start.elapsed(),
+            })
+        };
+
+        let part1 = parts
+            .part1()
+            .then(|| time(&|| day.try_part1()))
+            .transpose()?;
+        let part2 = parts
+            .part2()
+            .then(|| time(&|| day.try_part2()))
+            .transpose()?;
+
+        Ok(DayResult {
+            parse_time,
+            part1,
+            part2,
+        })
+    }
+
+    /// Like `run`, but on a thread of its own so a panicking or runaway solver can't take the
+    /// caller down with it. A solver still going after `timeout` is abandoned; its thread keeps
+    /// running in the background until the process exits.
+    pub fn run_isolated(
+        &'static self,
+        input: Vec<u8>,
+        parts: Parts,
+        timeout: Option<Duration>,
+    ) -> Result<DayResult, AocError> {
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name(format!("day{:02}", self.number))
+            .spawn(move || {
+                let res =
+                    panic::catch_unwind(AssertUnwindSafe(|| self.run(&mut &input[..], parts)));
+
+                // Nobody is listening anymore if we timed out
+                let _ = tx.send(res.unwrap_or_else(|payload| {
+                    Err(AocError::Panic(panic_message(payload.as_ref())))
+                }));
+            })?;
+
+        let res = match timeout {
+            Some(timeout) => rx.recv_timeout(timeout),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match res {
+            Ok(res) => res,
+            Err(RecvTimeoutError::Timeout) => Err(AocError::Timeout(timeout.unwrap_or_default())),
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(AocError::Panic("solver thread died".to_string()))
+            }
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(msg) = payload.downcast_ref::<&str>() {
+        msg.to_string()
+    } else if let Some(msg) = payload.downcast_ref::<String>() {
+        msg.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+const fn entry<D: Day + 'static>(number: u8, title: &'static str) -> DayEntry {
+    DayEntry {
+        number,
+        title,
+        new: D::new_dyn,
+    }
+}
+
+static REGISTRY: [DayEntry; 25] = [
+    entry::<Day01>(1, "Sonar Sweep"),
+    entry::<Day02>(2, "Dive!"),
+    entry::<Day03>(3, "Binary Diagnostic"),
+    entry::<Day04>(4, "Giant Squid"),
+    entry::<Day05>(5, "Hydrothermal Venture"),
+    entry::<Day06>(6, "Lanternfish"),
+    entry::<Day07>(7, "The Treachery of Whales"),
+    entry::<Day08>(8, "Seven Segment Search"),
+    entry::<Day09>(9, "Smoke Basin"),
+    entry::<Day10>(10, "Syntax Scoring"),
+    entry::<Day11>(11, "Dumbo Octopus"),
+    entry::<Day12>(12, "Passage Pathing"),
+    entry::<Day13>(13, "Transparent Origami"),
+    entry::<Day14>(14, "Extended Polymerization"),
+    entry::<Day15>(15, "Chiton"),
+    entry::<Day16>(16, "Packet Decoder"),
+    entry::<Day17>(17, "Trick Shot"),
+    entry::<Day18>(18, "Snailfish"),
+    entry::<Day19>(19, "Beacon Scanner"),
+    entry::<Day20>(20, "Trench Map"),
+    entry::<Day21>(21, "Dirac Dice"),
+    entry::<Day22>(22, "Reactor Reboot"),
+    entry::<Day23>(23, "Amphipod"),
+    entry::<Day24>(24, "Arithmetic Logic Unit"),
+    entry::<Day25>(25, "Sea Cucumber"),
+];
+
+/// Every solved day, sorted by number
+pub fn registry() -> &'static [DayEntry] {
+    &REGISTRY
+}
+
+/// Find the entry for a day, failing if it isn't solved
+pub fn lookup(day: u8) -> Result<&'static DayEntry, AocError> {
+    registry()
+        .iter()
+        .find(|entry| entry.number == day)
+        .ok_or(AocError::UnsupportedDay(day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Held by a test to keep `Blocking` from finishing until it lets go
+    static GATE: Mutex<()> = Mutex::new(());
+
+    /// A day that can't finish a part while `GATE` is held
+    struct Blocking;
+
+    impl Day for Blocking {
+        fn new<R: BufRead + ?Sized>(_reader: &mut R) -> Result<Self, AocError> {
+            Ok(Self)
+        }
+
+        fn part1(&self) -> String {
+            let _gate = GATE.lock().unwrap();
+            String::new()
+        }
+
+        fn part2(&self) -> String {
+            Day::part1(self)
+        }
+    }
+
+    static BLOCKING: DayEntry = entry::<Blocking>(0, "Blocking");
+
+    #[test]
+    fn test_registry() {
+        assert!(registry().windows(2).all(|w| w[0].number < w[1].number));
+
+        assert_eq!(lookup(18).unwrap().title, "Snailfish");
+        assert!(matches!(lookup(26), Err(AocError::UnsupportedDay(26))));
+
+        let day = (lookup(1).unwrap().new)(&mut &b"1\n3\n2\n"[..]).unwrap();
+        assert_eq!(day.part1(), "1");
+    }
+
+    #[test]
+    fn test_run_isolated() {
+        let res = lookup(1)
+            .unwrap()
+            .run_isolated(b"1\n3\n2\n".to_vec(), Parts::Both, None)
+            .unwrap();
+        assert_eq!(res.part2.unwrap().answer, "0");
+
+        // Scanners that share no beacons can't be placed
+        let input = b"--- scanner 0 ---\n1,2,3\n\n--- scanner 1 ---\n4,5,6\n";
+        let res = lookup(19)
+            .unwrap()
+            .run_isolated(input.to_vec(), Parts::Part1, None);
+        assert!(matches!(res, Err(AocError::Parse { msg, .. }) if msg.contains("scanner 1")));
+
+        // The solver can't finish until the gate opens, which is only after the timeout, and
+        // then its thread wraps up instead of lingering
+        let gate = GATE.lock().unwrap();
+        let timeout = Duration::from_millis(1);
+        let res = BLOCKING.run_isolated(vec![], Parts::Both, Some(timeout));
+        assert!(matches!(res, Err(AocError::Timeout(t)) if t == timeout));
+        drop(gate);
+    }
+}
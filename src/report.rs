@@ -0,0 +1,188 @@
+use crate::bench::millis;
+use crate::error::AocError;
+use crate::{DayResult, PartResult};
+
+use std::io::{self, Write};
+
+/// The outcome of running one input, as written by the machine readable formats
+pub struct Record<'a> {
+    pub day: u8,
+    pub input: &'a str,
+    pub result: &'a Result<DayResult, AocError>,
+}
+
+/// Field names, in the order they are written
+pub const FIELDS: [&str; 8] = [
+    "day", "input", "part1", "part2", "parse_ms", "p1_ms", "p2_ms", "error",
+];
+
+enum Value {
+    Number(String),
+    Text(String),
+}
+
+impl Record<'_> {
+    /// The value of each of `FIELDS`. Parts that weren't run and the error of a successful run
+    /// have no value.
+    fn values(&self) -> [Option<Value>; 8] {
+        let answer =
+            |part: &Option<PartResult>| part.as_ref().map(|part| Value::Text(part.answer.clone()));
+        let time =
+            |part: &Option<PartResult>| part.as_ref().map(|part| Value::Number(millis(part.time)));
+
+        let day = Some(Value::Number(self.day.to_string()));
+        let input = Some(Value::Text(self.input.to_string()));
+        match self.result {
+            Ok(res) => [
+                day,
+                input,
+                answer(&res.part1),
+                answer(&res.part2),
+                Some(Value::Number(millis(res.parse_time))),
+                time(&res.part1),
+                time(&res.part2),
+                None,
+            ],
+            Err(e) => [
+                day,
+                input,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(Value::Text(e.to_string())),
+            ],
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut res = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            c if c.is_control() => res.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+
+    res
+}
+
+/// Write the records as a JSON array with one object per line
+pub fn write_json<W: Write>(mut writer: W, records: &[Record]) -> io::Result<()> {
+    writeln!(writer, "[")?;
+    for (idx, record) in records.iter().enumerate() {
+        let fields: Vec<String> = FIELDS
+            .iter()
+            .zip(record.values())
+            .map(|(name, value)| {
+                let value = match value {
+                    Some(Value::Number(x)) => x,
+                    Some(Value::Text(s)) => json_string(&s),
+                    None => "null".to_string(),
+                };
+                format!("{}: {}", json_string(name), value)
+            })
+            .collect();
+
+        let sep = if idx + 1 < records.len() { "," } else { "" };
+        writeln!(writer, "  {{{}}}{}", fields.join(", "), sep)?;
+    }
+    writeln!(writer, "]")?;
+
+    Ok(())
+}
+
+/// Backslash escape anything that would break the row structure, so day 13's grid stays on
+/// one line
+fn tsv_field(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// Write the records as tab separated values with a header line. Missing values are empty.
+pub fn write_tsv<W: Write>(mut writer: W, records: &[Record]) -> io::Result<()> {
+    writeln!(writer, "{}", FIELDS.join("\t"))?;
+    for record in records {
+        let fields: Vec<String> = record
+            .values()
+            .into_iter()
+            .map(|value| match value {
+                Some(Value::Number(x)) => x,
+                Some(Value::Text(s)) => tsv_field(&s),
+                None => String::new(),
+            })
+            .collect();
+
+        writeln!(writer, "{}", fields.join("\t"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_write() {
+        let ok = Ok(DayResult {
+            parse_time: Duration::from_micros(1500),
+            part1: None,
+            part2: Some(PartResult {
+                answer: "#.\n\"#\"".to_string(),
+                time: Duration::from_millis(2),
+            }),
+        });
+        let err = Err(AocError::parse("bad\tinput").at_line(3));
+        let records = [
+            Record {
+                day: 13,
+                input: "real",
+                result: &ok,
+            },
+            Record {
+                day: 2,
+                input: "stdin",
+                result: &err,
+            },
+        ];
+
+        let mut json = vec![];
+        write_json(&mut json, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(json).unwrap(),
+            concat!(
+                "[\n",
+                "  {\"day\": 13, \"input\": \"real\", \"part1\": null, ",
+                "\"part2\": \"#.\\n\\\"#\\\"\", \"parse_ms\": 1.500, \"p1_ms\": null, ",
+                "\"p2_ms\": 2.000, \"error\": null},\n",
+                "  {\"day\": 2, \"input\": \"stdin\", \"part1\": null, \"part2\": null, ",
+                "\"parse_ms\": null, \"p1_ms\": null, \"p2_ms\": null, ",
+                "\"error\": \"line 3: bad\\tinput\"}\n",
+                "]\n",
+            )
+        );
+
+        let mut tsv = vec![];
+        write_tsv(&mut tsv, &records).unwrap();
+        assert_eq!(
+            String::from_utf8(tsv).unwrap(),
+            concat!(
+                "day\tinput\tpart1\tpart2\tparse_ms\tp1_ms\tp2_ms\terror\n",
+                "13\treal\t\t#.\\n\"#\"\t1.500\t\t2.000\t\n",
+                "2\tstdin\t\t\t\t\t\tline 3: bad\\tinput\n",
+            )
+        );
+    }
+}
@@ -1,51 +1,160 @@
-use std::error::Error;
+pub mod graph;
+mod parse;
+pub mod search;
+pub mod union_find;
+
+pub use self::parse::{
+    parse_csv, parse_lines, parsed_lines, read_blocks, read_csv, read_grid, Block,
+};
+
+use crate::error::AocError;
+
+use std::cmp;
 use std::fmt;
 use std::ops;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
-pub struct ParseInputError(pub String);
+/// Parse comma separated integers, reporting the (1-based) column of any bad one.
+fn parse_coords<const N: usize>(s: &str) -> Result<[i64; N], AocError> {
+    let mut coords = [0; N];
+    let mut parts = s.split(',');
+    let mut column = 1;
 
-impl fmt::Display for ParseInputError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+    for coord in coords.iter_mut() {
+        let part = parts
+            .next()
+            .ok_or_else(|| AocError::parse(format!("expected {} coordinates in {:?}", N, s)))?;
+
+        *coord = part
+            .parse()
+            .map_err(|e| AocError::from(e).at_column(column))?;
+        column += part.len() + 1;
     }
-}
 
-impl Error for ParseInputError {}
+    if parts.next().is_some() {
+        return Err(
+            AocError::parse(format!("expected {} coordinates in {:?}", N, s)).at_column(column - 1),
+        );
+    }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+    Ok(coords)
+}
+
+/// A point in 2D. The direction constants take y as pointing up.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct Point {
     pub x: i64,
     pub y: i64,
 }
 
 impl Point {
-    pub fn new(x: i64, y: i64) -> Self {
+    pub const ORIGIN: Point = Point::new(0, 0);
+    pub const NORTH: Point = Point::new(0, 1);
+    pub const EAST: Point = Point::new(1, 0);
+    pub const SOUTH: Point = Point::new(0, -1);
+    pub const WEST: Point = Point::new(-1, 0);
+    /// The four cardinal directions, clockwise from north
+    pub const CARDINALS: [Point; 4] = [Point::NORTH, Point::EAST, Point::SOUTH, Point::WEST];
+
+    pub const fn new(x: i64, y: i64) -> Self {
         Self { x, y }
     }
 
-    pub fn get_adj9(&self) -> impl Iterator<Item = Point> + '_ {
+    /// The 3x3 block centered on this point, including itself, with x as the outer loop
+    pub fn get_adj9(self) -> impl Iterator<Item = Point> {
         (-1..=1).flat_map(move |dx| (-1..=1).map(move |dy| Point::new(self.x + dx, self.y + dy)))
     }
+
+    /// The four orthogonal neighbors, clockwise from north
+    pub fn adj4(self) -> impl Iterator<Item = Point> {
+        Point::CARDINALS.into_iter().map(move |d| self + d)
+    }
+
+    /// The eight orthogonal and diagonal neighbors
+    pub fn adj8(self) -> impl Iterator<Item = Point> {
+        self.get_adj9().filter(move |&p| p != self)
+    }
+
+    pub fn manhattan(self, other: Point) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    pub fn chebyshev(self, other: Point) -> i64 {
+        cmp::max((self.x - other.x).abs(), (self.y - other.y).abs())
+    }
+
+    /// The sign of each coordinate, so a step from the origin towards this point
+    pub fn signum(self) -> Point {
+        Point::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Rotate 90° counterclockwise around the origin
+    pub fn rotate_left(self) -> Point {
+        Point::new(-self.y, self.x)
+    }
+
+    /// Rotate 90° clockwise around the origin
+    pub fn rotate_right(self) -> Point {
+        Point::new(self.y, -self.x)
+    }
+}
+
+impl ops::Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl ops::AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl ops::Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl ops::SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl ops::Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl ops::Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, k: i64) -> Self {
+        Point::new(self.x * k, self.y * k)
+    }
 }
 
 impl FromStr for Point {
-    type Err = ParseInputError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // TODO: simplify with itertools
-        let xy: Vec<&str> = s.split(',').collect();
-
-        assert!(xy.len() == 2);
-        let x = xy[0].parse().map_err(|_e| ParseInputError(s.to_string()))?;
-        let y = xy[1].parse().map_err(|_e| ParseInputError(s.to_string()))?;
+        let [x, y] = parse_coords(s)?;
 
         Ok(Point { x, y })
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+/// A point in 3D. Ordered lexicographically by `(x, y, z)`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub struct Point3D {
     coords: [i64; 3],
 }
@@ -55,13 +164,16 @@ impl Point3D {
         Self { coords: [x, y, z] }
     }
 
-    pub fn transform(&self, perm: [usize; 3], flips: [i64; 3]) -> Self {
-        let mut coords = [0; 3];
-        for idx in 0..3 {
-            coords[idx] = self.coords[perm[idx]] * flips[idx];
-        }
+    pub fn x(&self) -> i64 {
+        self.coords[0]
+    }
+
+    pub fn y(&self) -> i64 {
+        self.coords[1]
+    }
 
-        Self { coords }
+    pub fn z(&self) -> i64 {
+        self.coords[2]
     }
 
     pub fn manhattan_dist(&self, other: Self) -> i64 {
@@ -101,27 +213,427 @@ impl ops::Sub for Point3D {
     }
 }
 
+impl ops::Neg for Point3D {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            coords: self.coords.map(|c| -c),
+        }
+    }
+}
+
+impl ops::Mul<i64> for Point3D {
+    type Output = Self;
+
+    fn mul(self, k: i64) -> Self {
+        Self {
+            coords: self.coords.map(|c| c * k),
+        }
+    }
+}
+
 impl FromStr for Point3D {
-    type Err = ParseInputError;
+    type Err = AocError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // TODO: simplify with itertools
-        let xyz: Vec<&str> = s.split(',').collect();
+        let coords = parse_coords(s)?;
 
-        assert!(xyz.len() == 3);
-        let x = xyz[0]
-            .parse()
-            .map_err(|_e| ParseInputError(s.to_string()))?;
-        let y = xyz[1]
-            .parse()
-            .map_err(|_e| ParseInputError(s.to_string()))?;
-        let z = xyz[2]
-            .parse()
-            .map_err(|_e| ParseInputError(s.to_string()))?;
+        Ok(Self { coords })
+    }
+}
+
+/// A rotation of 3D space that maps axes onto axes, stored as a signed permutation matrix
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Rotation3 {
+    rows: [[i64; 3]; 3],
+}
+
+impl Rotation3 {
+    pub const IDENTITY: Self = Self {
+        rows: [[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+    };
+
+    /// The 24 proper rotations, starting with the identity. Reflections are left out.
+    pub fn all() -> impl Iterator<Item = Self> {
+        const PERMS: [[usize; 3]; 6] = [
+            [0, 1, 2],
+            [0, 2, 1],
+            [1, 0, 2],
+            [1, 2, 0],
+            [2, 0, 1],
+            [2, 1, 0],
+        ];
+
+        PERMS
+            .into_iter()
+            .flat_map(|perm| {
+                (0..8).map(move |flips: usize| {
+                    let mut rows = [[0; 3]; 3];
+                    for (idx, row) in rows.iter_mut().enumerate() {
+                        row[perm[idx]] = if flips >> idx & 1 == 1 { -1 } else { 1 };
+                    }
+
+                    Self { rows }
+                })
+            })
+            .filter(|rot| rot.det() == 1)
+    }
+
+    fn det(&self) -> i64 {
+        let m = &self.rows;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    pub fn apply(&self, p: Point3D) -> Point3D {
+        Point3D {
+            coords: self
+                .rows
+                .map(|row| row.iter().zip(p.coords).map(|(a, b)| a * b).sum()),
+        }
+    }
+
+    /// The rotation that applies `self` and then `other`
+    pub fn compose(&self, other: &Self) -> Self {
+        let mut rows = [[0; 3]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| other.rows[i][k] * self.rows[k][j]).sum();
+            }
+        }
+
+        Self { rows }
+    }
+
+    /// The rotation undoing `self`. Rotation matrices are orthogonal, so this is the transpose.
+    pub fn inverse(&self) -> Self {
+        let mut rows = [[0; 3]; 3];
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = self.rows[j][i];
+            }
+        }
 
-        Ok(Self::new(x, y, z))
+        Self { rows }
     }
 }
 
-pub type Grid = Vec<Vec<u8>>;
-pub type GridRef<'a> = &'a [Vec<u8>];
+/// A rectangular grid, stored row by row. Positions are `(row, col)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn from_fn<F: FnMut((usize, usize)) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let cells = (0..height)
+            .flat_map(|row| (0..width).map(move |col| (row, col)))
+            .map(&mut f)
+            .collect();
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Parse a non-empty rectangular grid, one row per line. `parse_cell` maps each character
+    /// to the stored value, or `None` if the character isn't allowed.
+    pub fn parse_with<F: Fn(char) -> Option<T>>(s: &str, parse_cell: F) -> Result<Self, AocError> {
+        let mut cells = vec![];
+        let mut width = None;
+        let mut height = 0;
+
+        for (line_idx, line) in s.lines().enumerate() {
+            let row_start = cells.len();
+            for (col_idx, c) in line.chars().enumerate() {
+                let cell = parse_cell(c).ok_or_else(|| {
+                    AocError::at(
+                        line_idx + 1,
+                        col_idx + 1,
+                        format!("unexpected character {:?}", c),
+                    )
+                })?;
+                cells.push(cell);
+            }
+
+            let row_len = cells.len() - row_start;
+            if *width.get_or_insert(row_len) != row_len {
+                return Err(AocError::parse(format!(
+                    "expected {} cells in every row",
+                    width.unwrap()
+                ))
+                .at_line(line_idx + 1));
+            }
+            height += 1;
+        }
+
+        let width = width.unwrap_or(0);
+        if width == 0 {
+            return Err(AocError::parse("empty grid").at_line(1));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of cells
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Index of `pos` in row major order, for per-cell side tables
+    pub fn flat_index(&self, (row, col): (usize, usize)) -> usize {
+        row * self.width + col
+    }
+
+    pub fn get(&self, (row, col): (usize, usize)) -> Option<&T> {
+        if row < self.height && col < self.width {
+            self.cells.get(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, (row, col): (usize, usize)) -> Option<&mut T> {
+        if row < self.height && col < self.width {
+            self.cells.get_mut(row * self.width + col)
+        } else {
+            None
+        }
+    }
+
+    /// Every position, in row major order
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |row| (0..width).map(move |col| (row, col)))
+    }
+
+    /// Every position along with its cell, in row major order
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> + '_ {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Each row as a slice, `height` of them even if they're empty
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).map(move |row| &self.cells[row * self.width..(row + 1) * self.width])
+    }
+
+    /// Each column top to bottom, `width` of them even if they're empty
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(move |col| self.cells.iter().skip(col).step_by(self.width))
+    }
+
+    fn offset_neighbors(
+        &self,
+        (row, col): (usize, usize),
+        offsets: &'static [(isize, isize)],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        offsets.iter().filter_map(move |&(dr, dc)| {
+            let row = row.checked_add_signed(dr).filter(|&row| row < height)?;
+            let col = col.checked_add_signed(dc).filter(|&col| col < width)?;
+            Some((row, col))
+        })
+    }
+
+    /// The up to 4 orthogonally adjacent positions inside the grid
+    pub fn neighbors4(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offset_neighbors(pos, &[(-1, 0), (0, -1), (0, 1), (1, 0)])
+    }
+
+    /// The up to 8 orthogonally or diagonally adjacent positions inside the grid
+    pub fn neighbors8(&self, pos: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.offset_neighbors(
+            pos,
+            &[
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+        )
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> ops::Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &T {
+        self.get(pos).expect("position out of bounds")
+    }
+}
+
+impl<T> ops::IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut T {
+        self.get_mut(pos).expect("position out of bounds")
+    }
+}
+
+/// A grid of single digits, such as a height map
+impl FromStr for Grid<u8> {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, |c| c.to_digit(10).map(|d| d as u8))
+    }
+}
+
+/// A grid of arbitrary characters
+impl FromStr for Grid<char> {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, Some)
+    }
+}
+
+/// One line per row, each cell written with its own `Display`
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                cell.fmt(f)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_point() {
+        let p = Point::new(3, -2);
+        assert_eq!(p + Point::NORTH * 2, Point::new(3, 0));
+        assert_eq!(p - p, Point::ORIGIN);
+        assert_eq!(-p, Point::new(-3, 2));
+        assert_eq!(p.manhattan(Point::ORIGIN), 5);
+        assert_eq!(p.chebyshev(Point::ORIGIN), 3);
+        assert_eq!(p.signum(), Point::new(1, -1));
+
+        assert_eq!(Point::NORTH.rotate_right(), Point::EAST);
+        assert_eq!(Point::NORTH.rotate_left(), Point::WEST);
+        assert_eq!(p.rotate_left().rotate_right(), p);
+
+        assert_eq!(p.adj4().count(), 4);
+        assert!(p.adj4().all(|q| q.manhattan(p) == 1));
+        assert_eq!(p.adj8().count(), 8);
+        assert!(p.adj8().all(|q| q.chebyshev(p) == 1));
+        assert_eq!(p.get_adj9().count(), 9);
+    }
+
+    #[test]
+    fn test_rotation3() {
+        let rotations: Vec<Rotation3> = Rotation3::all().collect();
+        assert_eq!(rotations.len(), 24);
+        assert_eq!(rotations[0], Rotation3::IDENTITY);
+        assert_eq!(rotations.iter().collect::<HashSet<_>>().len(), 24);
+
+        let p = Point3D::new(1, 2, 3);
+        let images: HashSet<Point3D> = rotations.iter().map(|rot| rot.apply(p)).collect();
+        assert_eq!(images.len(), 24);
+
+        for a in &rotations {
+            assert_eq!(a.compose(&a.inverse()), Rotation3::IDENTITY);
+            assert_eq!(a.inverse().apply(a.apply(p)), p);
+            for b in &rotations {
+                let ab = a.compose(b);
+                assert_eq!(ab.apply(p), b.apply(a.apply(p)));
+                assert!(rotations.contains(&ab));
+            }
+        }
+
+        assert_eq!(-p * 2, Point3D::new(-2, -4, -6));
+        assert_eq!((p.x(), p.y(), p.z()), (1, 2, 3));
+        assert!(p < Point3D::new(1, 3, 0));
+    }
+
+    #[test]
+    fn test_grid() {
+        let grid: Grid<u8> = "123\n456\n".parse().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get((1, 2)), Some(&6));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, 3)), None);
+
+        let mut nbs: Vec<_> = grid.neighbors4((0, 1)).collect();
+        nbs.sort_unstable();
+        assert_eq!(nbs, [(0, 0), (0, 2), (1, 1)]);
+        assert_eq!(grid.neighbors8((0, 0)).count(), 3);
+        assert_eq!(grid.neighbors8((1, 1)).count(), 5);
+
+        let rows: Vec<&[u8]> = grid.rows().collect();
+        assert_eq!(rows, [&[1, 2, 3][..], &[4, 5, 6][..]]);
+        let cols: Vec<Vec<u8>> = grid.cols().map(|col| col.copied().collect()).collect();
+        assert_eq!(cols, [[1, 4], [2, 5], [3, 6]]);
+
+        assert_eq!(grid.to_string(), "123\n456\n");
+
+        let grid: Grid<char> = ">.\nv.".parse().unwrap();
+        assert_eq!(grid[(1, 0)], 'v');
+
+        let err = "12\n3".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!(err.to_string(), "line 2: expected 2 cells in every row");
+        let err = "12\n3x".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!(err.line(), Some(2));
+        assert_eq!(err.column(), Some(2));
+        assert!("".parse::<Grid<u8>>().is_err());
+
+        let narrow = Grid::new(0, 3, 0u8);
+        assert!(narrow.rows().eq([&[][..]; 3]));
+        assert_eq!(narrow.cols().count(), 0);
+
+        let flat = Grid::new(3, 0, 0u8);
+        assert_eq!(flat.rows().count(), 0);
+        let cols: Vec<usize> = flat.cols().map(Iterator::count).collect();
+        assert_eq!(cols, [0, 0, 0]);
+    }
+}
@@ -0,0 +1,214 @@
+//! Undirected graphs with string labelled nodes. Names are interned to dense `NodeId`s so
+//! per-node data can live in plain vectors or bitmasks.
+
+use crate::error::AocError;
+
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    adj: Vec<Vec<NodeId>>,
+    // Whether each name is all lowercase or all uppercase, worked out once when it's interned
+    lower: Vec<bool>,
+    upper: Vec<bool>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The id of `name`, adding it as a new node if it hasn't been seen
+    pub fn intern(&mut self, name: &str) -> NodeId {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.adj.push(vec![]);
+        self.lower
+            .push(name.bytes().all(|c| c.is_ascii_lowercase()));
+        self.upper
+            .push(name.bytes().all(|c| c.is_ascii_uppercase()));
+
+        id
+    }
+
+    /// Add an undirected edge between `u` and `v`, interning both
+    pub fn add_edge(&mut self, u: &str, v: &str) -> (NodeId, NodeId) {
+        let (u, v) = (self.intern(u), self.intern(v));
+        self.adj[u].push(v);
+        if u != v {
+            self.adj[v].push(u);
+        }
+
+        (u, v)
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    /// Number of nodes
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = NodeId> {
+        0..self.len()
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> &[NodeId] {
+        &self.adj[id]
+    }
+
+    pub fn degree(&self, id: NodeId) -> usize {
+        self.adj[id].len()
+    }
+
+    /// Every edge once, as `(u, v)` with `u <= v`
+    pub fn edges(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.nodes().flat_map(move |u| {
+            self.adj[u]
+                .iter()
+                .filter(move |&&v| u <= v)
+                .map(move |&v| (u, v))
+        })
+    }
+
+    /// Is the node's name all lowercase (a small cave in day 12)
+    pub fn is_lower(&self, id: NodeId) -> bool {
+        self.lower[id]
+    }
+
+    /// Is the node's name all uppercase (a big cave in day 12)
+    pub fn is_upper(&self, id: NodeId) -> bool {
+        self.upper[id]
+    }
+
+    /// Number of edges from `start` to every node, `None` for unreachable ones
+    pub fn bfs(&self, start: NodeId) -> Vec<Option<usize>> {
+        let mut dist = vec![None; self.len()];
+        dist[start] = Some(0);
+
+        let mut queue = VecDeque::from([start]);
+        while let Some(u) = queue.pop_front() {
+            let next_dist = dist[u].map(|d| d + 1);
+            for &v in &self.adj[u] {
+                if dist[v].is_none() {
+                    dist[v] = next_dist;
+                    queue.push_back(v);
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Nodes reachable from `start` in depth first preorder
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut seen = vec![false; self.len()];
+        let mut order = vec![];
+
+        let mut stack = vec![start];
+        while let Some(u) = stack.pop() {
+            if seen[u] {
+                continue;
+            }
+            seen[u] = true;
+            order.push(u);
+
+            // Reversed so neighbors are visited in insertion order
+            stack.extend(self.adj[u].iter().rev().filter(|&&v| !seen[v]));
+        }
+
+        order
+    }
+
+    /// The connected components, each in depth first order
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        let mut seen = vec![false; self.len()];
+        let mut components = vec![];
+
+        for u in self.nodes() {
+            if !seen[u] {
+                let component = self.dfs(u);
+                for &v in &component {
+                    seen[v] = true;
+                }
+                components.push(component);
+            }
+        }
+
+        components
+    }
+}
+
+/// Parses one `a-b` edge per line
+impl FromStr for Graph {
+    type Err = AocError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut graph = Graph::new();
+
+        for (line_idx, line) in s.lines().enumerate() {
+            match line.split_once('-') {
+                Some((u, v)) if !u.is_empty() && !v.is_empty() && !v.contains('-') => {
+                    graph.add_edge(u, v);
+                }
+                _ => {
+                    return Err(AocError::parse(format!("expected `a-b`, found {:?}", line))
+                        .at_line(line_idx + 1))
+                }
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph() {
+        let graph: Graph = "start-A\nstart-b\nA-c\nA-b\nb-d\nA-end\nb-end\nx-Y"
+            .parse()
+            .unwrap();
+
+        assert_eq!(graph.len(), 8);
+        let start = graph.id("start").unwrap();
+        let a = graph.id("A").unwrap();
+        let end = graph.id("end").unwrap();
+        assert_eq!(graph.name(a), "A");
+        assert_eq!(graph.degree(a), 4);
+        assert_eq!(graph.neighbors(start), [a, graph.id("b").unwrap()]);
+        assert!(graph.is_lower(start) && !graph.is_lower(a) && graph.is_upper(a));
+        assert_eq!(graph.edges().count(), 8);
+
+        let dist = graph.bfs(start);
+        assert_eq!(dist[end], Some(2));
+        assert_eq!(dist[graph.id("Y").unwrap()], None);
+        assert_eq!(graph.dfs(start).len(), 6);
+        assert_eq!(graph.components().len(), 2);
+
+        let err = "a-b\nc".parse::<Graph>().unwrap_err();
+        assert_eq!(err.line(), Some(2));
+    }
+}
@@ -0,0 +1,147 @@
+//! Helpers for the common input shapes: one value per line, comma separated numbers, blocks
+//! separated by blank lines and grids. Errors carry the (1-based) line they happened on.
+
+use super::Grid;
+use crate::error::{at_line, AocError};
+
+use std::io::BufRead;
+use std::str::FromStr;
+
+/// Parse every line of the input as a `T`, lazily
+pub fn parsed_lines<T, R>(reader: R) -> impl Iterator<Item = Result<T, AocError>>
+where
+    T: FromStr,
+    AocError: From<T::Err>,
+    R: BufRead,
+{
+    reader
+        .lines()
+        .enumerate()
+        .map(|(line_idx, line)| line?.parse().map_err(at_line(line_idx + 1)))
+}
+
+/// Parse every line of the input as a `T`
+pub fn parse_lines<T, R>(reader: &mut R) -> Result<Vec<T>, AocError>
+where
+    T: FromStr,
+    AocError: From<T::Err>,
+    R: BufRead + ?Sized,
+{
+    parsed_lines(reader).collect()
+}
+
+/// Parse comma separated values, reporting the (1-based) column of any bad one
+pub fn parse_csv<T>(s: &str) -> Result<Vec<T>, AocError>
+where
+    T: FromStr,
+    AocError: From<T::Err>,
+{
+    let mut column = 1;
+
+    s.split(',')
+        .map(|part| {
+            let res = part
+                .parse()
+                .map_err(|e| AocError::from(e).at_column(column));
+            column += part.len() + 1;
+
+            res
+        })
+        .collect()
+}
+
+/// Read a single line of comma separated values, like `3,4,3,1,2`
+pub fn read_csv<T, R>(reader: &mut R) -> Result<Vec<T>, AocError>
+where
+    T: FromStr,
+    AocError: From<T::Err>,
+    R: BufRead + ?Sized,
+{
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    parse_csv(line.trim_end()).map_err(at_line(1))
+}
+
+/// A run of non-blank lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    /// Line number of the first line in the block
+    pub first_line: usize,
+    pub lines: Vec<String>,
+}
+
+impl Block {
+    /// Each line with its line number
+    pub fn numbered(&self) -> impl Iterator<Item = (usize, &str)> {
+        (self.first_line..).zip(self.lines.iter().map(String::as_str))
+    }
+}
+
+/// Split the input into blocks separated by blank lines. Runs of blank lines count as one.
+pub fn read_blocks<R: BufRead + ?Sized>(reader: &mut R) -> Result<Vec<Block>, AocError> {
+    let mut blocks: Vec<Block> = vec![];
+    let mut in_block = false;
+
+    for (line_idx, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            in_block = false;
+            continue;
+        }
+
+        if !in_block {
+            blocks.push(Block {
+                first_line: line_idx + 1,
+                lines: vec![],
+            });
+            in_block = true;
+        }
+        blocks.last_mut().unwrap().lines.push(line);
+    }
+
+    Ok(blocks)
+}
+
+/// Read a whole input as a grid of digits (`Grid<u8>`) or characters (`Grid<char>`)
+pub fn read_grid<T, R>(reader: &mut R) -> Result<Grid<T>, AocError>
+where
+    Grid<T>: FromStr<Err = AocError>,
+    R: BufRead + ?Sized,
+{
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+
+    s.parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let nums: Vec<i64> = parse_lines(&mut "1\n-2\n3\n".as_bytes()).unwrap();
+        assert_eq!(nums, [1, -2, 3]);
+        let err = parse_lines::<i64, _>(&mut "1\nx\n".as_bytes()).unwrap_err();
+        assert_eq!(err.line(), Some(2));
+
+        assert_eq!(
+            read_csv::<u8, _>(&mut "3,4,3\n".as_bytes()).unwrap(),
+            [3, 4, 3]
+        );
+        let err = read_csv::<u8, _>(&mut "3,40,300\n".as_bytes()).unwrap_err();
+        assert_eq!((err.line(), err.column()), (Some(1), Some(6)));
+
+        let blocks = read_blocks(&mut "a\n\nb\nc\n\n\nd\n".as_bytes()).unwrap();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(
+            blocks[1].numbered().collect::<Vec<_>>(),
+            [(3, "b"), (4, "c")]
+        );
+        assert_eq!(blocks[2].first_line, 7);
+
+        let grid: Grid<u8> = read_grid(&mut "12\n34\n".as_bytes()).unwrap();
+        assert_eq!(grid[(1, 0)], 3);
+    }
+}
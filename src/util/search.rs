@@ -0,0 +1,375 @@
+//! Shortest paths over implicit graphs. States are anything hashable, and edges come from a
+//! successor function returning `(next_state, weight)` pairs.
+//!
+//! Distances are kept in a `Table`. `Paths` also remembers how each state was reached so the
+//! path can be rebuilt, a plain `HashMap<S, u64>` only keeps distances, and `Dense` keeps them in
+//! a vector for states that map onto `0..capacity`.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::Hash;
+
+/// Where a search keeps the best known distance to each state
+pub trait Table<S> {
+    /// Best known distance to `state`, if it's been reached
+    fn get(&self, state: &S) -> Option<u64>;
+
+    /// Record `next` as reachable at `dist`, through `from` unless it's the start, if that's an
+    /// improvement
+    fn relax(&mut self, from: Option<&S>, next: &S, dist: u64) -> bool;
+}
+
+/// Distances only, for when the path itself isn't needed
+impl<S: Clone + Eq + Hash> Table<S> for HashMap<S, u64> {
+    fn get(&self, state: &S) -> Option<u64> {
+        HashMap::get(self, state).copied()
+    }
+
+    fn relax(&mut self, _from: Option<&S>, next: &S, dist: u64) -> bool {
+        match self.get_mut(next) {
+            Some(cur) if *cur <= dist => false,
+            Some(cur) => {
+                *cur = dist;
+                true
+            }
+            None => {
+                self.insert(next.clone(), dist);
+                true
+            }
+        }
+    }
+}
+
+/// Distances along with the state each state was last reached from
+#[derive(Debug, Clone)]
+pub struct Paths<S> {
+    pub dist: HashMap<S, u64>,
+    pub prev: HashMap<S, S>,
+}
+
+impl<S> Default for Paths<S> {
+    fn default() -> Self {
+        Self {
+            dist: HashMap::new(),
+            prev: HashMap::new(),
+        }
+    }
+}
+
+impl<S: Clone + Eq + Hash> Paths<S> {
+    /// The states from the start to `target`, inclusive
+    pub fn path(&self, target: &S) -> Option<Vec<S>> {
+        if !self.dist.contains_key(target) {
+            return None;
+        }
+
+        let mut path = vec![target.clone()];
+        while let Some(prev) = self.prev.get(path.last().unwrap()) {
+            path.push(prev.clone());
+        }
+        path.reverse();
+
+        Some(path)
+    }
+}
+
+impl<S: Clone + Eq + Hash> Table<S> for Paths<S> {
+    fn get(&self, state: &S) -> Option<u64> {
+        self.dist.get(state).copied()
+    }
+
+    fn relax(&mut self, from: Option<&S>, next: &S, dist: u64) -> bool {
+        if !self.dist.relax(from, next, dist) {
+            return false;
+        }
+
+        if let Some(from) = from {
+            self.prev.insert(next.clone(), from.clone());
+        }
+
+        true
+    }
+}
+
+/// Distances in a vector, for states with a cheap one to one mapping onto `0..capacity`
+#[derive(Debug, Clone)]
+pub struct Dense<F> {
+    index: F,
+    dist: Vec<u64>,
+}
+
+impl<F> Dense<F> {
+    pub fn new(capacity: usize, index: F) -> Self {
+        Self {
+            index,
+            dist: vec![u64::MAX; capacity],
+        }
+    }
+}
+
+impl<S, F: Fn(&S) -> usize> Table<S> for Dense<F> {
+    fn get(&self, state: &S) -> Option<u64> {
+        let dist = self.dist[(self.index)(state)];
+        (dist != u64::MAX).then_some(dist)
+    }
+
+    fn relax(&mut self, _from: Option<&S>, next: &S, dist: u64) -> bool {
+        let cur = &mut self.dist[(self.index)(next)];
+        if *cur <= dist {
+            return false;
+        }
+        *cur = dist;
+
+        true
+    }
+}
+
+/// Result of searching from a single start state
+#[derive(Debug, Clone)]
+pub struct Search<S, T = Paths<S>> {
+    /// The first goal state reached, if any
+    pub goal: Option<S>,
+    /// Best known distance to every state seen. Exact for every state closer than the goal.
+    pub table: T,
+}
+
+impl<S, T: Table<S>> Search<S, T> {
+    fn new(start: &S, mut table: T) -> Self {
+        table.relax(None, start, 0);
+
+        Self { goal: None, table }
+    }
+
+    fn is_stale(&self, state: &S, dist: u64) -> bool {
+        self.table.get(state) != Some(dist)
+    }
+
+    pub fn goal_dist(&self) -> Option<u64> {
+        self.table.get(self.goal.as_ref()?)
+    }
+}
+
+impl<S: Clone + Eq + Hash> Search<S> {
+    /// The states from the start to the goal, inclusive
+    pub fn goal_path(&self) -> Option<Vec<S>> {
+        self.table.path(self.goal.as_ref()?)
+    }
+
+    /// The states from the start to `target`, inclusive
+    pub fn path(&self, target: &S) -> Option<Vec<S>> {
+        self.table.path(target)
+    }
+}
+
+/// Heap entry ordered so `BinaryHeap` pops the lowest priority first
+struct Entry<S> {
+    priority: u64,
+    dist: u64,
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Break ties towards the deeper state, it's likely closer to the goal
+        (other.priority, self.dist).cmp(&(self.priority, other.dist))
+    }
+}
+
+/// A* search. `heuristic` must never overestimate the distance to a goal, or the answer may
+/// not be the shortest. Stops at the first goal reached, or explores everything reachable if
+/// there is none.
+pub fn astar<S, I, N, H, G>(start: S, successors: N, heuristic: H, is_goal: G) -> Search<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+    N: FnMut(&S) -> I,
+    H: FnMut(&S) -> u64,
+    G: FnMut(&S) -> bool,
+{
+    astar_with(Paths::default(), start, successors, heuristic, is_goal)
+}
+
+/// `astar`, keeping distances in `table`
+pub fn astar_with<S, T, I, N, H, G>(
+    table: T,
+    start: S,
+    mut successors: N,
+    mut heuristic: H,
+    mut is_goal: G,
+) -> Search<S, T>
+where
+    T: Table<S>,
+    I: IntoIterator<Item = (S, u64)>,
+    N: FnMut(&S) -> I,
+    H: FnMut(&S) -> u64,
+    G: FnMut(&S) -> bool,
+{
+    let mut search = Search::new(&start, table);
+
+    let mut heap = BinaryHeap::new();
+    heap.push(Entry {
+        priority: heuristic(&start),
+        dist: 0,
+        state: start,
+    });
+
+    while let Some(Entry { dist, state, .. }) = heap.pop() {
+        if search.is_stale(&state, dist) {
+            continue;
+        }
+
+        if is_goal(&state) {
+            search.goal = Some(state);
+            break;
+        }
+
+        for (next, weight) in successors(&state) {
+            let next_dist = dist + weight;
+            if search.table.relax(Some(&state), &next, next_dist) {
+                heap.push(Entry {
+                    priority: next_dist + heuristic(&next),
+                    dist: next_dist,
+                    state: next,
+                });
+            }
+        }
+    }
+
+    search
+}
+
+/// Dijkstra's algorithm, see `astar`
+pub fn dijkstra<S, I, N, G>(start: S, successors: N, is_goal: G) -> Search<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+    N: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, successors, |_| 0, is_goal)
+}
+
+/// Dijkstra's algorithm with a bucket queue (Dial's algorithm), for small integer weights.
+/// Every edge weight must be at most `max_weight`. Weight 0 edges are fine, so this doubles as
+/// a 0-1 BFS.
+pub fn dial<S, I, N, G>(start: S, max_weight: u64, successors: N, is_goal: G) -> Search<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = (S, u64)>,
+    N: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    dial_with(Paths::default(), start, max_weight, successors, is_goal)
+}
+
+/// `dial`, keeping distances in `table`
+pub fn dial_with<S, T, I, N, G>(
+    table: T,
+    start: S,
+    max_weight: u64,
+    mut successors: N,
+    mut is_goal: G,
+) -> Search<S, T>
+where
+    T: Table<S>,
+    I: IntoIterator<Item = (S, u64)>,
+    N: FnMut(&S) -> I,
+    G: FnMut(&S) -> bool,
+{
+    let mut search = Search::new(&start, table);
+
+    // Every queued distance is within max_weight of the current one, so the buckets can wrap
+    let num_buckets = max_weight as usize + 1;
+    let mut buckets: Vec<Vec<S>> = (0..num_buckets).map(|_| vec![]).collect();
+    buckets[0].push(start);
+    let mut queued = 1;
+
+    let mut dist = 0;
+    while queued > 0 {
+        let bucket = dist as usize % num_buckets;
+        while let Some(state) = buckets[bucket].pop() {
+            queued -= 1;
+            if search.is_stale(&state, dist) {
+                continue;
+            }
+
+            if is_goal(&state) {
+                search.goal = Some(state);
+                return search;
+            }
+
+            for (next, weight) in successors(&state) {
+                assert!(
+                    weight <= max_weight,
+                    "edge weight {} above {}",
+                    weight,
+                    max_weight
+                );
+                if search.table.relax(Some(&state), &next, dist + weight) {
+                    buckets[(dist + weight) as usize % num_buckets].push(next);
+                    queued += 1;
+                }
+            }
+        }
+
+        dist += 1;
+    }
+
+    search
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        // 0 -> 1 -> 3 is shorter than 0 -> 3, and 2 is a dead end
+        let edges = |&u: &u32| -> Vec<(u32, u64)> {
+            match u {
+                0 => vec![(1, 1), (3, 5), (2, 0)],
+                1 => vec![(3, 2)],
+                3 => vec![(4, 1)],
+                _ => vec![],
+            }
+        };
+
+        for search in [
+            dijkstra(0, edges, |&u| u == 3),
+            astar(0, edges, |&u| 3 - u.min(3) as u64, |&u| u == 3),
+            dial(0, 5, edges, |&u| u == 3),
+        ] {
+            assert_eq!(search.goal, Some(3));
+            assert_eq!(search.goal_dist(), Some(3));
+            assert_eq!(search.goal_path(), Some(vec![0, 1, 3]));
+            assert_eq!(search.path(&2), Some(vec![0, 2]));
+        }
+
+        let search = dial(0, 5, edges, |_| false);
+        assert_eq!(search.goal, None);
+        assert_eq!(search.table.dist[&4], 4);
+        assert_eq!(search.path(&7), None);
+
+        // The cheaper tables find the same distances
+        let search = astar_with(HashMap::new(), 0, edges, |_| 0, |&u| u == 3);
+        assert_eq!(search.goal_dist(), Some(3));
+        let search = dial_with(Dense::new(5, |&u: &u32| u as usize), 0, 5, edges, |_| false);
+        assert_eq!(search.goal_dist(), None);
+        assert_eq!(search.table.get(&4), Some(4));
+        assert_eq!(search.table.get(&2), Some(0));
+    }
+}
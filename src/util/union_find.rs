@@ -0,0 +1,155 @@
+//! Disjoint sets (union find) with union by size and path compression.
+
+/// Disjoint sets over the indices `0..n`
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    // parent[x] is the index if >= 0 and negative size if < 0
+    parent: Vec<i64>,
+    components: usize,
+}
+
+impl UnionFind {
+    pub fn new(n: usize) -> Self {
+        Self {
+            parent: vec![-1; n],
+            components: n,
+        }
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The representative of `idx`'s set. Iterative, so long chains can't overflow the stack.
+    pub fn find(&mut self, idx: usize) -> usize {
+        let mut root = idx;
+        while self.parent[root] >= 0 {
+            root = self.parent[root] as usize;
+        }
+
+        // Path compression
+        let mut cur = idx;
+        while cur != root {
+            let next = self.parent[cur] as usize;
+            self.parent[cur] = root as i64;
+            cur = next;
+        }
+
+        root
+    }
+
+    /// Join the sets of `u` and `v`. Returns false if they were already the same set.
+    pub fn merge(&mut self, u: usize, v: usize) -> bool {
+        let mut ur = self.find(u);
+        let mut vr = self.find(v);
+        if ur == vr {
+            return false;
+        }
+
+        // Union by size: hang the smaller tree off the larger one
+        if self.parent[ur] > self.parent[vr] {
+            std::mem::swap(&mut ur, &mut vr);
+        }
+        self.parent[ur] += self.parent[vr];
+        self.parent[vr] = ur as i64;
+        self.components -= 1;
+
+        true
+    }
+
+    pub fn same(&mut self, u: usize, v: usize) -> bool {
+        self.find(u) == self.find(v)
+    }
+
+    /// Size of the set containing `idx`
+    pub fn size_of(&mut self, idx: usize) -> usize {
+        let root = self.find(idx);
+        (-self.parent[root]) as usize
+    }
+
+    /// Number of disjoint sets
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Sizes of every set, in no particular order
+    pub fn get_sizes(&self) -> Vec<i64> {
+        self.parent
+            .iter()
+            .filter_map(|&val| if val < 0 { Some(-val) } else { None })
+            .collect()
+    }
+
+    /// Every set's members in increasing order. Sets are ordered by their smallest member.
+    pub fn groups(&mut self) -> impl Iterator<Item = Vec<usize>> {
+        // Map each root to its group's position, assigned in order of first appearance
+        let mut group_of = vec![usize::MAX; self.len()];
+        let mut groups: Vec<Vec<usize>> = Vec::with_capacity(self.components);
+
+        for idx in 0..self.len() {
+            let root = self.find(idx);
+            if group_of[root] == usize::MAX {
+                group_of[root] = groups.len();
+                groups.push(vec![]);
+            }
+            groups[group_of[root]].push(idx);
+        }
+
+        groups.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One set where each element's parent is the next one, the deepest tree there is. Union by
+    /// size never builds these, so they're made by hand.
+    fn chain(n: usize) -> UnionFind {
+        let mut parent: Vec<i64> = (1..=n as i64).collect();
+        parent[n - 1] = -(n as i64);
+
+        UnionFind {
+            parent,
+            components: 1,
+        }
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut uf = UnionFind::new(6);
+        assert_eq!(uf.component_count(), 6);
+
+        assert!(uf.merge(0, 3));
+        assert!(uf.merge(4, 3));
+        assert!(uf.merge(1, 5));
+        assert!(!uf.merge(0, 4));
+
+        assert!(uf.same(4, 0));
+        assert!(!uf.same(1, 0));
+        assert_eq!(uf.size_of(3), 3);
+        assert_eq!(uf.size_of(2), 1);
+        assert_eq!(uf.component_count(), 3);
+
+        let mut sizes = uf.get_sizes();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [1, 2, 3]);
+        assert_eq!(
+            uf.groups().collect::<Vec<_>>(),
+            [vec![0, 3, 4], vec![1, 5], vec![2]]
+        );
+
+        // A long chain shouldn't overflow the stack, and is flattened by the first find
+        let n = 1_000_000;
+        let mut uf = chain(n);
+        assert_eq!(uf.find(0), n - 1);
+        assert!(uf.parent[..n - 1].iter().all(|&p| p == (n - 1) as i64));
+        assert_eq!(uf.size_of(0), n);
+        assert_eq!(uf.component_count(), 1);
+    }
+}